@@ -0,0 +1,273 @@
+//! FRC-0042 method numbers computed by `const fn`
+//!
+//! A blake2b-512 implementation that can run at compile time, so that method numbers can be used
+//! in `const` items and const generics without the `method_hash!` proc macro, and without paying
+//! for a hashing syscall during dispatch. Only `core` is used, so this module is available with
+//! `default-features = false` in `no_std` crates.
+//!
+//! ```
+//! use frc42_hasher::const_hash::method_number;
+//!
+//! const TRANSFER: u64 = method_number("Transfer");
+//! assert_eq!(method_number("Constructor"), 1);
+//! ```
+
+use crate::convention::Convention;
+
+const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const SIGMA: [[usize; 16]; 12] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+];
+
+const BLOCK_LENGTH: usize = 128;
+const DIGEST_LENGTH: usize = 64;
+
+const CONSTRUCTOR_METHOD_NAME: &[u8] = b"Constructor";
+const CONSTRUCTOR_METHOD_NUMBER: u64 = 1;
+
+/// Computes the FRC-0042 method number of a method name
+///
+/// Agrees with [`MethodResolver`](crate::hash::MethodResolver) using a blake2b hasher. Invalid
+/// names (see the FRC-0042 naming rules) panic, which is a compile error when evaluated in a const
+/// context.
+pub const fn method_number(method_name: &str) -> u64 {
+    method_number_with(&Convention::V1, method_name)
+}
+
+/// Computes the method number of a method name under a specific hashing convention
+pub const fn method_number_with(convention: &Convention, method_name: &str) -> u64 {
+    let name = method_name.as_bytes();
+    check_method_name(name);
+    if bytes_eq(name, CONSTRUCTOR_METHOD_NAME) {
+        return CONSTRUCTOR_METHOD_NUMBER;
+    }
+
+    let digest = digest(convention.prefix().as_bytes(), name);
+    let mut i = 0;
+    while i < DIGEST_LENGTH {
+        let method_id =
+            u32::from_be_bytes([digest[i], digest[i + 1], digest[i + 2], digest[i + 3]]) as u64;
+        // Method numbers below the first method number are reserved for other use
+        if method_id >= convention.first_method_number() {
+            return method_id;
+        }
+        i += 4;
+    }
+    panic!("unable to calculate method id, choose a another method name")
+}
+
+/// Computes the blake2b-512 digest of some bytes
+pub const fn blake2b_512(bytes: &[u8]) -> [u8; 64] {
+    digest(&[], bytes)
+}
+
+/// Panics if a method name doesn't follow the FRC-0042 naming rules
+const fn check_method_name(name: &[u8]) {
+    if name.is_empty() {
+        panic!("empty method name provided");
+    }
+    if !(name[0].is_ascii_uppercase() || name[0] == b'_') {
+        panic!("method name doesn't start with capital letter or _");
+    }
+    let mut i = 0;
+    while i < name.len() {
+        if !(name[i].is_ascii_alphanumeric() || name[i] == b'_') {
+            panic!("method name contains letters outside [a-zA-Z0-9_]");
+        }
+        i += 1;
+    }
+}
+
+const fn bytes_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Hashes `prefix` followed by `bytes` without copying them into one buffer
+const fn digest(prefix: &[u8], bytes: &[u8]) -> [u8; 64] {
+    let length = prefix.len() + bytes.len();
+    let mut h = IV;
+    // parameter block: no key, 64 byte digest
+    h[0] ^= 0x01010000 ^ DIGEST_LENGTH as u64;
+
+    // the last block is always compressed with the final flag set, even if it's empty or full
+    let blocks = if length == 0 { 1 } else { length.div_ceil(BLOCK_LENGTH) };
+    let mut block = 0;
+    while block < blocks {
+        let mut m = [0u64; 16];
+        let mut i = 0;
+        while i < BLOCK_LENGTH {
+            let offset = block * BLOCK_LENGTH + i;
+            let byte = if offset < prefix.len() {
+                prefix[offset]
+            } else if offset < length {
+                bytes[offset - prefix.len()]
+            } else {
+                0
+            };
+            m[i / 8] |= (byte as u64) << (8 * (i % 8));
+            i += 1;
+        }
+
+        let last = block == blocks - 1;
+        let counter = if last { length } else { (block + 1) * BLOCK_LENGTH };
+        compress(&mut h, &m, counter as u128, last);
+        block += 1;
+    }
+
+    let mut out = [0u8; 64];
+    let mut i = 0;
+    while i < DIGEST_LENGTH {
+        out[i] = (h[i / 8] >> (8 * (i % 8))) as u8;
+        i += 1;
+    }
+    out
+}
+
+const fn compress(h: &mut [u64; 8], m: &[u64; 16], counter: u128, last: bool) {
+    let mut v = [0u64; 16];
+    let mut i = 0;
+    while i < 8 {
+        v[i] = h[i];
+        v[i + 8] = IV[i];
+        i += 1;
+    }
+    v[12] ^= counter as u64;
+    v[13] ^= (counter >> 64) as u64;
+    if last {
+        v[14] = !v[14];
+    }
+
+    let mut round = 0;
+    while round < 12 {
+        let s = &SIGMA[round];
+        mix(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+        mix(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+        mix(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+        mix(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+        mix(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+        mix(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+        mix(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+        mix(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+        round += 1;
+    }
+
+    let mut i = 0;
+    while i < 8 {
+        h[i] ^= v[i] ^ v[i + 8];
+        i += 1;
+    }
+}
+
+const fn mix(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{blake2b_512, method_number, method_number_with};
+    use crate::convention::Convention;
+
+    const NAME: u64 = method_number("Name");
+
+    /// A const generic parameter computed from a method name
+    struct Method<const N: u64>;
+    impl<const N: u64> Method<N> {
+        fn number(&self) -> u64 {
+            N
+        }
+    }
+
+    #[test]
+    fn computes_method_numbers() {
+        assert_eq!(method_number("Constructor"), 1);
+        assert_eq!(NAME, 0x02ea015c);
+        assert_eq!(method_number("Receive"), 0xde180de3);
+        assert_eq!(Method::<{ method_number("Receive") }>.number(), 0xde180de3);
+        // both names of a known collision
+        assert_eq!(method_number("Method46776"), 0x0977a84c);
+        assert_eq!(method_number("Method85157"), 0x0977a84c);
+    }
+
+    #[test]
+    fn computes_versioned_method_numbers() {
+        assert_eq!(method_number_with(&Convention::V1, "Receive"), 0xde180de3);
+        let private = Convention::V1.with_prefix("acme|");
+        assert_ne!(method_number_with(&private, "Receive"), 0xde180de3);
+        assert_eq!(method_number_with(&private, "Constructor"), 1);
+    }
+
+    #[test]
+    fn hashes_known_vectors() {
+        // from RFC 7693
+        assert_eq!(blake2b_512(b"abc")[..8], [0xba, 0x80, 0xa5, 0x3f, 0x98, 0x1c, 0x4d, 0x0d]);
+        assert_eq!(blake2b_512(b"")[..8], [0x78, 0x6a, 0x02, 0xf7, 0x42, 0x01, 0x59, 0x03]);
+    }
+
+    #[test]
+    #[cfg(feature = "blake2b")]
+    fn matches_method_resolver() {
+        use crate::hash::{Blake2bHasher, MethodResolver};
+
+        let resolver = MethodResolver::new(Blake2bHasher {});
+        // names spanning block boundaries, including the 2 byte prefix
+        for length in [1, 125, 126, 127, 128, 254, 255, 300] {
+            let name = format!("M{}", "a".repeat(length - 1));
+            assert_eq!(method_number(&name), resolver.method_number(&name).unwrap());
+            let private = Convention::V1.with_prefix("acme|");
+            assert_eq!(
+                method_number_with(&private, &name),
+                MethodResolver::with_convention(Blake2bHasher {}, private)
+                    .method_number(&name)
+                    .unwrap()
+            );
+            assert_eq!(
+                blake2b_512(name.as_bytes()).to_vec(),
+                blake2b_simd::blake2b(name.as_bytes()).as_bytes().to_vec()
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't start with capital letter")]
+    fn rejects_invalid_names() {
+        method_number("notValid");
+    }
+}
@@ -0,0 +1,88 @@
+//! Versions of the FRC-0042 method hashing convention
+//!
+//! A method number is the first 4 byte chunk of `hash(prefix + name)` that isn't below a reserved
+//! cut-off. FRC-0042 itself is version 1, with the prefix `1|` and numbers below 2^24 reserved.
+//! A future revision of the standard, or a private namespace such as a per-organisation prefix,
+//! can be used by resolving names with a different [`Convention`].
+
+use alloc::borrow::Cow;
+
+/// The domain separator and reserved range used to derive method numbers
+///
+/// The prefix may be borrowed or owned, so a convention can be built from a prefix only known at
+/// runtime (e.g. one passed on the command line or to a proc macro).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Convention {
+    prefix: Cow<'static, str>,
+    first_method_number: u64,
+}
+
+impl Convention {
+    /// The convention specified by FRC-0042
+    pub const V1: Convention =
+        Convention { prefix: Cow::Borrowed("1|"), first_method_number: 1 << 24 };
+
+    /// The most recent version of the FRC-0042 convention
+    pub const LATEST: Convention = Convention::V1;
+
+    /// Creates a custom convention, e.g. for a private namespace
+    ///
+    /// Method numbers below `first_method_number` are never produced, so it should be at most
+    /// 2^32 for names to resolve at all.
+    pub fn new(prefix: impl Into<Cow<'static, str>>, first_method_number: u64) -> Self {
+        Self { prefix: prefix.into(), first_method_number }
+    }
+
+    /// Looks up a published version of the convention
+    pub const fn version(version: u32) -> Option<Self> {
+        match version {
+            1 => Some(Self::V1),
+            _ => None,
+        }
+    }
+
+    /// Creates a convention with the reserved range of `self` but a different prefix
+    pub fn with_prefix(&self, prefix: impl Into<Cow<'static, str>>) -> Self {
+        Self::new(prefix, self.first_method_number)
+    }
+
+    /// The domain separator prepended to method names before hashing
+    pub const fn prefix(&self) -> &str {
+        match &self.prefix {
+            Cow::Borrowed(prefix) => prefix,
+            Cow::Owned(prefix) => prefix.as_str(),
+        }
+    }
+
+    /// The smallest method number a name can resolve to, other than the constructor
+    pub const fn first_method_number(&self) -> u64 {
+        self.first_method_number
+    }
+}
+
+impl Default for Convention {
+    fn default() -> Self {
+        Self::V1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Convention;
+
+    #[test]
+    fn looks_up_versions() {
+        assert_eq!(Convention::version(1), Some(Convention::V1));
+        assert_eq!(Convention::version(2), None);
+        assert_eq!(Convention::default(), Convention::LATEST);
+        assert_eq!(Convention::V1.prefix(), "1|");
+        assert_eq!(Convention::V1.first_method_number(), 1 << 24);
+
+        let private = Convention::V1.with_prefix("acme|");
+        assert_eq!(private, Convention::new("acme|", 1 << 24));
+        // prefixes only known at runtime are owned
+        let owned = Convention::V1.with_prefix(String::from("acme|"));
+        assert_eq!(owned, private);
+        assert_eq!(owned.prefix(), "acme|");
+    }
+}
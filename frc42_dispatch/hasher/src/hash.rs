@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::convention::Convention;
+
 /// Minimal interface for a hashing function
 ///
 /// Hasher::hash() must return a digest that is at least 4 bytes long so that it can be cast to a
@@ -23,11 +25,24 @@ impl Hasher for Blake2bSyscall {
     }
 }
 
+/// Hasher that computes blake2b natively, for use outside of the FVM (e.g. in off-chain tools)
+#[cfg(feature = "blake2b")]
+#[derive(Default, Clone, Copy, Debug)]
+pub struct Blake2bHasher {}
+
+#[cfg(feature = "blake2b")]
+impl Hasher for Blake2bHasher {
+    fn hash(&self, bytes: &[u8]) -> Vec<u8> {
+        blake2b_simd::blake2b(bytes).as_bytes().to_vec()
+    }
+}
+
 /// Uses an underlying hashing function (blake2b by convention) to generate method numbers from
 /// method names
-#[derive(Default)]
+#[derive(Default, Clone, Debug)]
 pub struct MethodResolver<T: Hasher> {
     hasher: T,
+    convention: Convention,
 }
 
 #[derive(Error, PartialEq, Eq, Debug)]
@@ -51,19 +66,29 @@ pub enum IllegalNameErr {
 impl<T: Hasher> MethodResolver<T> {
     const CONSTRUCTOR_METHOD_NAME: &'static str = "Constructor";
     const CONSTRUCTOR_METHOD_NUMBER: u64 = 1_u64;
-    const FIRST_METHOD_NUMBER: u64 = 1 << 24;
     const DIGEST_CHUNK_LENGTH: usize = 4;
 
     /// Creates a MethodResolver with an instance of a hasher (blake2b by convention)
     pub fn new(hasher: T) -> Self {
-        Self { hasher }
+        Self::with_convention(hasher, Convention::V1)
+    }
+
+    /// Creates a MethodResolver for a specific version of the hashing convention
+    pub fn with_convention(hasher: T, convention: Convention) -> Self {
+        Self { hasher, convention }
+    }
+
+    /// The hashing convention used to resolve names
+    pub fn convention(&self) -> &Convention {
+        &self.convention
     }
 
     /// Generates a standard FRC-0042 compliant method number
     ///
-    /// The method number is calculated as the first four bytes of `hash(method-name)`.
-    /// The name `Constructor` is always hashed to 1 and other method names that hash to
-    /// 0 or 1 are avoided via rejection sampling.
+    /// The method number is calculated as the first four bytes of `hash(prefix + method-name)`,
+    /// where the prefix is `1|` unless another [`Convention`] was given. The name `Constructor` is
+    /// always hashed to 1 and other method names that hash into the reserved range are avoided via
+    /// rejection sampling.
     pub fn method_number(&self, method_name: &str) -> Result<u64, MethodNameErr> {
         check_method_name(method_name)?;
 
@@ -71,7 +96,7 @@ impl<T: Hasher> MethodResolver<T> {
             return Ok(Self::CONSTRUCTOR_METHOD_NUMBER);
         }
 
-        let method_name = format!("1|{method_name}");
+        let method_name = format!("{}{method_name}", self.convention.prefix());
         let digest = self.hasher.hash(method_name.as_bytes());
 
         for chunk in digest.chunks(Self::DIGEST_CHUNK_LENGTH) {
@@ -81,8 +106,8 @@ impl<T: Hasher> MethodResolver<T> {
             }
 
             let method_id = as_u32(chunk) as u64;
-            // Method numbers below FIRST_METHOD_NUMBER are reserved for other use
-            if method_id >= Self::FIRST_METHOD_NUMBER {
+            // Method numbers below the first method number are reserved for other use
+            if method_id >= self.convention.first_method_number() {
                 return Ok(method_id);
             }
         }
@@ -126,6 +151,7 @@ fn as_u32(bytes: &[u8]) -> u32 {
 mod tests {
 
     use super::{Hasher, IllegalNameErr, MethodNameErr, MethodResolver};
+    use crate::convention::Convention;
 
     #[derive(Clone, Copy)]
     struct FakeHasher {}
@@ -157,6 +183,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn uses_the_convention_prefix() {
+        let fake_hasher = FakeHasher {};
+        let private = Convention::V1.with_prefix("acme|");
+        let method_hasher = MethodResolver::with_convention(fake_hasher, private.clone());
+        assert_eq!(method_hasher.convention(), &private);
+        assert_eq!(
+            method_hasher.method_number("NormalMethod").unwrap(),
+            super::as_u32(&fake_hasher.hash(b"acme|NormalMethod")) as u64
+        );
+        assert_eq!(method_hasher.method_number("Constructor").unwrap(), 1);
+
+        // no 4 byte chunk can reach the cut-off
+        let unreachable = Convention::new("1|", u64::MAX);
+        let method_hasher = MethodResolver::with_convention(fake_hasher, unreachable);
+        assert_eq!(
+            method_hasher.method_number("NormalMethod").unwrap_err(),
+            MethodNameErr::IndeterminableId
+        );
+    }
+
     #[test]
     fn disallows_invalid_method_names() {
         let method_hasher = MethodResolver::new(FakeHasher {});
@@ -216,4 +263,13 @@ mod tests {
         // But the method number is not a collision
         assert_ne!(method_hasher_1.method_number(contrived_1).unwrap(), 1);
     }
+
+    #[test]
+    #[cfg(feature = "blake2b")]
+    fn blake2b_method_numbers() {
+        let resolver = MethodResolver::new(super::Blake2bHasher {});
+        assert_eq!(resolver.method_number("Constructor").unwrap(), 1);
+        assert_eq!(resolver.method_number("Name").unwrap(), 0x02ea015c);
+        assert_eq!(resolver.method_number("Receive").unwrap(), 0xde180de3);
+    }
 }
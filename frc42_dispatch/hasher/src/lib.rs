@@ -1 +1,8 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod const_hash;
+pub mod convention;
+#[cfg(feature = "std")]
 pub mod hash;
@@ -0,0 +1,202 @@
+use frc42_syntax::{is_unit, parse_convention, returns, MethodAttr, Returns};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::Parser;
+use syn::spanned::Spanned;
+use syn::{Error, FnArg, Ident, ImplItem, ImplItemMethod, ItemImpl, LitStr, Result, Type};
+
+/// The way a method's return value is turned into the block id returned from `invoke`
+enum Output {
+    /// No return value, so no block is returned
+    Unit,
+    /// A serializable value
+    Value,
+    /// A `Result`, where errors abort with an exit code
+    Result {
+        /// Whether the `Ok` type is `()`
+        unit: bool,
+    },
+}
+
+/// A method marked with `#[method("Name")]` inside an `#[actor]` impl block
+struct ExportedMethod {
+    name: LitStr,
+    number: u64,
+    ident: Ident,
+    param: Option<Type>,
+    output: Output,
+    /// The type of data returned, for the manifest
+    returns: Option<Type>,
+}
+
+pub fn expand(attr: TokenStream, mut item: ItemImpl) -> Result<TokenStream> {
+    let convention = parse_convention.parse2(attr)?.unwrap_or_default();
+    if !item.generics.params.is_empty() {
+        return Err(Error::new(item.generics.span(), "#[actor] cannot be used on a generic impl"));
+    }
+
+    let mut methods: Vec<ExportedMethod> = Vec::new();
+    for impl_item in item.items.iter_mut() {
+        if let ImplItem::Method(method) = impl_item {
+            if let Some(MethodAttr { name, result }) = take_method_attr(method)? {
+                let number = crate::method_number_with(&name, &convention)?;
+                if let Some(existing) = methods.iter().find(|m| m.number == number) {
+                    let message = if existing.name.value() == name.value() {
+                        format!("duplicate method name \"{}\"", name.value())
+                    } else {
+                        format!(
+                            "method names \"{}\" and \"{}\" both resolve to method number {:#x}",
+                            existing.name.value(),
+                            name.value(),
+                            number
+                        )
+                    };
+                    return Err(Error::new(name.span(), message));
+                }
+                methods.push(parse_method(name, number, result, method)?);
+            }
+        }
+    }
+
+    let self_ty = &item.self_ty;
+    let arms = methods.iter().map(|method| {
+        let number = method.number;
+        let ident = &method.ident;
+        let call = match &method.param {
+            Some(ty) => quote! {
+                <#self_ty>::#ident(::frc42_dispatch::entrypoint::deserialize_params::<#ty>(params))
+            },
+            None => quote! { <#self_ty>::#ident() },
+        };
+        let body = match method.output {
+            Output::Unit => quote! {
+                #call;
+                ::frc42_dispatch::entrypoint::NO_DATA_BLOCK_ID
+            },
+            Output::Value => quote! {
+                ::frc42_dispatch::entrypoint::return_ipld(&#call)
+            },
+            Output::Result { unit: true } => quote! {
+                match #call {
+                    ::core::result::Result::Ok(_) => ::frc42_dispatch::entrypoint::NO_DATA_BLOCK_ID,
+                    ::core::result::Result::Err(e) => ::frc42_dispatch::entrypoint::abort_with_error(&e),
+                }
+            },
+            Output::Result { unit: false } => quote! {
+                match #call {
+                    ::core::result::Result::Ok(ret) => ::frc42_dispatch::entrypoint::return_ipld(&ret),
+                    ::core::result::Result::Err(e) => ::frc42_dispatch::entrypoint::abort_with_error(&e),
+                }
+            },
+        };
+        quote! { #number => { #body } }
+    });
+
+    let actor_name = quote!(#self_ty).to_string();
+    let manifest_methods = methods.iter().map(|method| {
+        let name = &method.name;
+        let number = method.number;
+        let params = type_name(&method.param);
+        let returns = type_name(&method.returns);
+        quote! {
+            ::frc42_dispatch::manifest::MethodManifest {
+                name: ::std::string::String::from(#name),
+                number: #number,
+                params: #params,
+                returns: #returns,
+            }
+        }
+    });
+
+    Ok(quote! {
+        #item
+
+        impl ::frc42_dispatch::manifest::HasActorManifest for #self_ty {
+            fn actor_manifest() -> ::frc42_dispatch::manifest::ActorManifest {
+                ::frc42_dispatch::manifest::ActorManifest {
+                    name: ::frc42_dispatch::manifest::normalize_type_name(#actor_name),
+                    methods: ::std::vec![#(#manifest_methods),*],
+                }
+            }
+        }
+
+        #[no_mangle]
+        pub fn invoke(params: u32) -> u32 {
+            let method = ::frc42_dispatch::entrypoint::method_number();
+            match method {
+                #(#arms)*
+                _ => ::frc42_dispatch::entrypoint::abort_unhandled(method),
+            }
+        }
+    })
+}
+
+/// Removes the `#[method("Name")]` attribute from a method, returning its arguments if it was
+/// present
+fn take_method_attr(method: &mut ImplItemMethod) -> Result<Option<MethodAttr>> {
+    let mut name = None;
+    let mut result = Ok(());
+    method.attrs.retain(|attr| {
+        if !attr.path.is_ident("method") {
+            return true;
+        }
+        match attr.parse_args::<MethodAttr>() {
+            Ok(_) if name.is_some() => {
+                result = Err(Error::new(attr.span(), "duplicate #[method] attribute"));
+            }
+            Ok(args) => name = Some(args),
+            Err(e) => result = Err(e),
+        }
+        false
+    });
+    result.map(|_| name)
+}
+
+fn parse_method(
+    name: LitStr,
+    number: u64,
+    result: bool,
+    method: &ImplItemMethod,
+) -> Result<ExportedMethod> {
+    let sig = &method.sig;
+    let mut param = None;
+    for (i, input) in sig.inputs.iter().enumerate() {
+        match input {
+            FnArg::Receiver(receiver) => {
+                return Err(Error::new(
+                    receiver.span(),
+                    "exported methods are associated functions and cannot take self",
+                ))
+            }
+            FnArg::Typed(_) if i > 0 => {
+                return Err(Error::new(
+                    input.span(),
+                    "exported methods take at most one parameter, use a struct to pass more",
+                ))
+            }
+            FnArg::Typed(pat_type) => param = Some((*pat_type.ty).clone()),
+        }
+    }
+
+    let (output, returns) = match returns(&sig.output, result) {
+        Returns::Unit => (Output::Unit, None),
+        Returns::Value(ty) => (Output::Value, Some(ty.clone())),
+        Returns::Result(Some(ok)) if is_unit(ok) => (Output::Result { unit: true }, None),
+        Returns::Result(ok) => (Output::Result { unit: false }, ok.cloned()),
+    };
+
+    Ok(ExportedMethod { name, number, ident: sig.ident.clone(), param, output, returns })
+}
+
+/// Renders an optional type as an `Option<String>` expression for the manifest
+fn type_name(ty: &Option<Type>) -> TokenStream {
+    match ty {
+        Some(ty) => {
+            let name = quote!(#ty).to_string();
+            quote! {
+                ::std::option::Option::Some(::frc42_dispatch::manifest::normalize_type_name(#name))
+            }
+        }
+        None => quote! { ::std::option::Option::None },
+    }
+}
@@ -1,39 +1,235 @@
-use frc42_hasher::hash::MethodResolver;
+use frc42_hasher::convention::Convention;
+use frc42_hasher::hash::{Blake2bHasher, MethodNameErr, MethodResolver};
+use frc42_syntax::parse_convention;
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::parse::{Parse, ParseStream};
-use syn::{parse_macro_input, LitStr, Result};
+use syn::{bracketed, parse_macro_input, token, Error, Ident, ItemImpl, LitStr, Result, Token};
 
-mod hash;
-use crate::hash::Blake2bHasher;
+mod actor;
 
-struct MethodName(LitStr);
+struct MethodName {
+    name: LitStr,
+    convention: Convention,
+}
 
 impl MethodName {
     /// Hash the method name
-    fn hash(&self) -> u64 {
-        let resolver = MethodResolver::new(Blake2bHasher {});
-        resolver.method_number(&self.0.value()).unwrap()
+    fn hash(&self) -> Result<u64> {
+        method_number_with(&self.name, &self.convention)
+    }
+}
+
+/// Resolves the method number of a name under a hashing convention, reporting invalid names as an
+/// error on the literal
+pub(crate) fn method_number_with(name: &LitStr, convention: &Convention) -> Result<u64> {
+    let resolver = MethodResolver::with_convention(Blake2bHasher {}, convention.clone());
+    resolver.method_number(&name.value()).map_err(|e| {
+        let message = match (e, suggest_name(&name.value())) {
+            (MethodNameErr::IllegalName(e), Some(suggestion)) => format!(
+                "invalid method name \"{}\": {}, did you mean \"{}\"?",
+                name.value(),
+                e,
+                suggestion
+            ),
+            (MethodNameErr::IllegalName(e), None) => {
+                format!("invalid method name \"{}\": {}", name.value(), e)
+            }
+            (e, _) => e.to_string(),
+        };
+        Error::new(name.span(), message)
+    })
+}
+
+/// Suggests a valid method name for an invalid one
+///
+/// Characters outside `[a-zA-Z0-9_]` are dropped and the letter following them is capitalised,
+/// so "transfer-from" becomes "TransferFrom". Names starting with a digit get a leading `_`.
+fn suggest_name(name: &str) -> Option<String> {
+    let mut suggestion = String::with_capacity(name.len());
+    let mut capitalise = true;
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            suggestion.push(if capitalise { c.to_ascii_uppercase() } else { c });
+            capitalise = false;
+        } else {
+            capitalise = true;
+        }
     }
+    if suggestion.starts_with(|c: char| c.is_ascii_digit()) {
+        suggestion.insert(0, '_');
+    }
+    MethodResolver::new(Blake2bHasher {}).method_number(&suggestion).ok().map(|_| suggestion)
 }
 
 impl Parse for MethodName {
     fn parse(input: ParseStream) -> Result<Self> {
         let lookahead = input.lookahead1();
-
-        if lookahead.peek(LitStr) {
-            input.parse().map(MethodName)
-        } else {
-            Err(lookahead.error())
+        if !lookahead.peek(LitStr) {
+            return Err(lookahead.error());
         }
+        let name = input.parse()?;
+
+        // optional `version = N` or `prefix = "..."` selecting the hashing convention
+        let convention = match input.parse::<Option<Token![,]>>()? {
+            Some(_) => parse_convention(input)?,
+            None => None,
+        };
+
+        Ok(MethodName { name, convention: convention.unwrap_or_default() })
     }
 }
 
+/// Computes the FRC-0042 method number of a method name at compile time
+///
+/// Names are resolved with version 1 of the convention unless another is chosen with
+/// `version = N`, or a private namespace with `prefix = "..."` (keeping the version 1 reserved
+/// range).
+///
+/// ```ignore
+/// const TRANSFER: u64 = method_hash!("Transfer");
+/// const ACME_TRANSFER: u64 = method_hash!("Transfer", prefix = "acme|");
+/// ```
 #[proc_macro]
 pub fn method_hash(input: TokenStream) -> TokenStream {
     let name: MethodName = parse_macro_input!(input);
-    let hash = name.hash();
-    quote!(#hash).into()
+    match name.hash() {
+        Ok(hash) => quote!(#hash).into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// A name passed to `check_method_names!`, marked with a leading `if` if it belongs to a guarded
+/// match arm or with `exports` if it was added for the exports arm
+struct CheckedName {
+    guarded: bool,
+    generated: bool,
+    name: LitStr,
+}
+
+impl Parse for CheckedName {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let guarded = input.parse::<Option<Token![if]>>()?.is_some();
+        let generated = input.peek(Ident) && input.parse::<Ident>()? == "exports";
+        let name = input.parse()?;
+        Ok(CheckedName { guarded, generated, name })
+    }
+}
+
+/// Checks that a list of method names resolve to distinct method numbers
+///
+/// Used by `match_method!` so that duplicate names or two names that hash to the same method
+/// number are reported at compile time rather than producing unreachable match arms. Names from
+/// guarded arms are marked with `if`: they may repeat another name, but must not collide with a
+/// different one. Names added by the macro are marked with `exports` and clashes with them are
+/// reported on the other name, as they have no span of their own. Names may be separated by
+/// commas. The names may be preceded by a bracketed list of `method_hash!` convention arguments,
+/// such as `[, prefix = "acme|"]`. Invalid names are left for `method_hash!` to report.
+#[doc(hidden)]
+#[proc_macro]
+pub fn check_method_names(input: TokenStream) -> TokenStream {
+    let parser = |input: ParseStream| {
+        let mut convention = None;
+        if input.peek(token::Bracket) {
+            let content;
+            bracketed!(content in input);
+            content.parse::<Option<Token![,]>>()?;
+            convention = parse_convention(&content)?;
+        }
+        let mut names = Vec::new();
+        while !input.is_empty() {
+            names.push(input.parse::<CheckedName>()?);
+            input.parse::<Option<Token![,]>>()?;
+        }
+        Ok((convention.unwrap_or_default(), names))
+    };
+    let (convention, names) = parse_macro_input!(input with parser);
+    let resolver = MethodResolver::with_convention(Blake2bHasher {}, convention);
+
+    let mut seen: Vec<(&CheckedName, u64)> = Vec::new();
+    let mut errors: Option<Error> = None;
+    for checked in names.iter() {
+        let name = &checked.name;
+        let number = match resolver.method_number(&name.value()) {
+            Ok(number) => number,
+            Err(_) => continue,
+        };
+        let clash = seen.iter().find(|(other, n)| {
+            // guarded arms may repeat a name, but the exports arm is matched before any of them
+            let repeatable =
+                (other.guarded || checked.guarded) && !(other.generated || checked.generated);
+            *n == number && (other.name.value() != name.value() || !repeatable)
+        });
+        let message = match clash {
+            Some((other, _)) if other.name.value() == name.value() => {
+                format!("duplicate method name \"{}\"", name.value())
+            }
+            Some((other, _)) => format!(
+                "method names \"{}\" and \"{}\" both resolve to method number {:#x}",
+                other.name.value(),
+                name.value(),
+                number
+            ),
+            None => {
+                seen.push((checked, number));
+                continue;
+            }
+        };
+        let span = match clash {
+            Some((other, _)) if checked.generated => other.name.span(),
+            _ => name.span(),
+        };
+        let error = Error::new(span, message);
+        match errors.as_mut() {
+            Some(errors) => errors.combine(error),
+            None => errors = Some(error),
+        }
+    }
+
+    match errors {
+        Some(errors) => errors.to_compile_error().into(),
+        None => TokenStream::new(),
+    }
+}
+
+/// Generates an actor's `invoke` entry point from an impl block
+///
+/// Associated functions marked with `#[method("Name")]` are exported under their FRC-0042 method
+/// number. Each may take a single parameter, deserialized from the message params, and return
+/// nothing, a serializable value or a `Result` whose error converts into an `ExitCode`. Messages
+/// for any other method number abort with `USR_UNHANDLED_MESSAGE`.
+///
+/// Names are hashed with version 1 of the convention unless the attribute selects another, e.g.
+/// `#[actor(prefix = "acme|")]`, taking the same arguments as `method_hash!`.
+///
+/// Only return types named `Result` are treated as fallible. Methods returning an alias such as
+/// `ActorResult<T>` must be marked with `#[method("Name", result)]`, otherwise the whole value is
+/// returned.
+///
+/// The actor type also implements `HasActorManifest`, whose `actor_manifest()` returns an
+/// `ActorManifest` of the exported methods.
+///
+/// The generated code calls into `frc42_dispatch::entrypoint`, so the actor must enable the
+/// `use_sdk` feature of `frc42_dispatch`.
+///
+/// ```ignore
+/// struct GreeterActor;
+///
+/// #[frc42_dispatch::actor]
+/// impl GreeterActor {
+///     #[method("Constructor")]
+///     fn constructor() {}
+///
+///     #[method("Greet")]
+///     fn greet(name: String) -> String {
+///         format!("Hello, {name}")
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn actor(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let item: ItemImpl = parse_macro_input!(item);
+    actor::expand(attr.into(), item).unwrap_or_else(|e| e.to_compile_error()).into()
 }
 
 #[cfg(test)]
@@ -58,5 +254,24 @@ mod tests {
         let t = trybuild::TestCases::new();
         t.compile_fail("tests/naming/illegal-chars.rs");
         t.compile_fail("tests/naming/non-capital-start.rs");
+        t.compile_fail("tests/naming/digit-start.rs");
+        t.compile_fail("tests/naming/kebab-case.rs");
+        t.compile_fail("tests/naming/unknown-version.rs");
+    }
+
+    #[test]
+    fn bad_actors() {
+        let t = trybuild::TestCases::new();
+        t.compile_fail("tests/actor/method-with-receiver.rs");
+        t.compile_fail("tests/actor/too-many-params.rs");
+        t.compile_fail("tests/actor/invalid-name.rs");
+    }
+
+    #[test]
+    fn method_collisions() {
+        let t = trybuild::TestCases::new();
+        t.pass("tests/collisions/distinct-names.rs");
+        t.compile_fail("tests/collisions/duplicate-name.rs");
+        t.compile_fail("tests/collisions/hash-collision.rs");
     }
 }
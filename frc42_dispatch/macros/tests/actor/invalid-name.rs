@@ -0,0 +1,12 @@
+use frc42_macros::actor;
+
+struct Actor;
+
+// should fail because the method name starts with a non-capital letter
+#[actor]
+impl Actor {
+    #[method("transfer")]
+    fn transfer(amount: u64) {}
+}
+
+fn main() {}
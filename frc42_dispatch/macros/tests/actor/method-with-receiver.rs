@@ -0,0 +1,12 @@
+use frc42_macros::actor;
+
+struct Actor;
+
+// should fail because exported methods cannot take self
+#[actor]
+impl Actor {
+    #[method("Constructor")]
+    fn constructor(&self) {}
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use frc42_macros::actor;
+
+struct Actor;
+
+// should fail because exported methods take a single params struct
+#[actor]
+impl Actor {
+    #[method("Transfer")]
+    fn transfer(to: u64, amount: u64) {}
+}
+
+fn main() {}
@@ -1,3 +1,5 @@
+use frc42_hasher::const_hash::method_number_with;
+use frc42_hasher::convention::Convention;
 use frc42_macros::method_hash;
 
 fn main() {
@@ -18,4 +20,13 @@ fn main() {
     assert_eq!(method_hash!("TransferFrom"), 0xd7d4deed);
     assert_eq!(method_hash!("Transfer"), 0x04cbf732);
     assert_eq!(method_hash!("Mint"), 0x06f84ab2);
+
+    // explicit hashing conventions
+    assert_eq!(method_hash!("Transfer", version = 1), 0x04cbf732);
+    assert_eq!(method_hash!("Transfer", prefix = "1|",), 0x04cbf732);
+    assert_eq!(
+        method_hash!("Transfer", prefix = "acme|"),
+        method_number_with(&Convention::V1.with_prefix("acme|"), "Transfer")
+    );
+    assert_eq!(method_hash!("Constructor", prefix = "acme|"), 1);
 }
@@ -0,0 +1,5 @@
+use frc42_macros::check_method_names;
+
+fn main() {
+	check_method_names!("Constructor", "Transfer", "Burn", "Mint");
+}
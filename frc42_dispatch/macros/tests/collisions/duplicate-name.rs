@@ -0,0 +1,6 @@
+use frc42_macros::check_method_names;
+
+fn main() {
+	// should fail because "Transfer" is listed twice
+	check_method_names!("Constructor", "Transfer", "Burn", "Transfer");
+}
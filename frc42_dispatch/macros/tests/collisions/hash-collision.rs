@@ -0,0 +1,6 @@
+use frc42_macros::check_method_names;
+
+fn main() {
+	// should fail because both names hash to 0x977a84c
+	check_method_names!("Method46776", "Method85157");
+}
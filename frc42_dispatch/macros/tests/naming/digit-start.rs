@@ -0,0 +1,6 @@
+use frc42_macros::method_hash;
+
+fn main() {
+	// should fail because the name starts with a digit
+	let _str_hash = method_hash!("2ndTransfer");
+}
@@ -1,6 +1,6 @@
 use frc42_macros::method_hash;
 
 fn main() {
-	// this should panic due to empty string
+	// this should fail due to empty string
 	let _str_hash = method_hash!("");
 }
@@ -0,0 +1,6 @@
+use frc42_macros::method_hash;
+
+fn main() {
+	// should fail because the name is kebab-case
+	let _str_hash = method_hash!("transfer-from");
+}
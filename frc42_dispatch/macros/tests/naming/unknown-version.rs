@@ -0,0 +1,6 @@
+use frc42_macros::method_hash;
+
+fn main() {
+	// should fail because there is no version 2 of the convention yet
+	let _str_hash = method_hash!("Transfer", version = 2);
+}
@@ -0,0 +1,74 @@
+//! Runtime support for actor entry points
+//!
+//! These helpers are used by the `invoke` function generated by [`actor`](crate::actor) but can
+//! equally be called from a hand-written `invoke`. Failures abort the message with an exit code
+//! instead of returning, as there is no caller to report an error to.
+use std::fmt::Display;
+
+use fvm_ipld_encoding::{de::DeserializeOwned, ser::Serialize, DAG_CBOR};
+use fvm_sdk as sdk;
+use fvm_shared::{error::ExitCode, MethodNum};
+
+use crate::interface::{supports_interface, InterfaceId};
+
+pub use sdk::NO_DATA_BLOCK_ID;
+
+/// Returns the method number of the message currently being processed
+pub fn method_number() -> MethodNum {
+    sdk::message::method_number()
+}
+
+/// Grab the incoming parameters and deserialize them from CBOR
+///
+/// Aborts with `USR_ILLEGAL_ARGUMENT` if no parameters were provided and with `USR_SERIALIZATION`
+/// if they could not be decoded into `O`.
+pub fn deserialize_params<O: DeserializeOwned>(params: u32) -> O {
+    let params = match sdk::message::params_raw(params) {
+        Ok(Some(params)) => params,
+        Ok(None) => abort(ExitCode::USR_ILLEGAL_ARGUMENT, "method expects parameters"),
+        Err(e) => abort(ExitCode::USR_ILLEGAL_ARGUMENT, &format!("failed to read params: {e}")),
+    };
+    params.deserialize().unwrap_or_else(|e| {
+        abort(ExitCode::USR_SERIALIZATION, &format!("failed to deserialize params: {e}"))
+    })
+}
+
+/// Serialize a return value and store it as a block, returning the block id
+///
+/// Aborts with `USR_SERIALIZATION` if the value could not be stored.
+pub fn return_ipld<T>(value: &T) -> u32
+where
+    T: Serialize + ?Sized,
+{
+    let bytes = fvm_ipld_encoding::to_vec(value).unwrap_or_else(|e| {
+        abort(ExitCode::USR_SERIALIZATION, &format!("failed to serialize return value: {e}"))
+    });
+    sdk::ipld::put_block(DAG_CBOR, bytes.as_slice()).unwrap_or_else(|e| {
+        abort(ExitCode::USR_SERIALIZATION, &format!("failed to store return value: {e}"))
+    })
+}
+
+/// Handle a `SupportsInterface` call for an actor implementing `interfaces`, returning the block id
+/// of the answer
+pub fn handle_supports_interface(interfaces: &[InterfaceId], params: u32) -> u32 {
+    let id: InterfaceId = deserialize_params(params);
+    return_ipld(&supports_interface(interfaces, id))
+}
+
+/// Abort with the exit code and message derived from an error
+pub fn abort_with_error<E>(error: &E) -> !
+where
+    for<'a> ExitCode: From<&'a E>,
+    E: Display,
+{
+    abort(ExitCode::from(error), &error.to_string())
+}
+
+/// Abort because the method number isn't exported by this actor
+pub fn abort_unhandled(method: MethodNum) -> ! {
+    abort(ExitCode::USR_UNHANDLED_MESSAGE, &format!("unrecognized method number {method}"))
+}
+
+fn abort(exit_code: ExitCode, message: &str) -> ! {
+    sdk::vm::abort(exit_code.value(), Some(message))
+}
@@ -0,0 +1,56 @@
+//! The standard `Exports` introspection method
+//!
+//! Actors built with [`match_method!`](crate::match_method) can answer `Exports` with the name and
+//! method number of every method they dispatch, by adding an `exports(methods) => ...` arm. The
+//! list is generated from the other arms of the same match, so it always agrees with what the
+//! actor actually handles.
+use fvm_shared::MethodNum;
+
+use crate::const_hash::method_number;
+
+/// Name of the introspection method
+pub const EXPORTS_METHOD_NAME: &str = "Exports";
+
+/// Method number of the introspection method
+pub const EXPORTS_METHOD_NUM: MethodNum = method_number(EXPORTS_METHOD_NAME);
+
+/// The (name, method number) pairs returned by `Exports`, in dispatch order
+pub type Exports = Vec<(String, MethodNum)>;
+
+/// Collects (name, method number) pairs into an [`Exports`] listing, skipping repeated names
+///
+/// `match_method!` passes the numbers computed at compile time with `method_hash!`.
+pub fn exports(methods: &[(&str, MethodNum)]) -> Exports {
+    let mut exports: Exports = Vec::with_capacity(methods.len());
+    for (name, number) in methods.iter() {
+        if !exports.iter().any(|(n, _)| n == name) {
+            exports.push((name.to_string(), *number));
+        }
+    }
+    exports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{exports, EXPORTS_METHOD_NUM};
+    use crate::method_hash;
+
+    #[test]
+    fn lists_exports() {
+        assert_eq!(EXPORTS_METHOD_NUM, method_hash!("Exports"));
+        let transfer = method_hash!("Transfer");
+        assert_eq!(
+            exports(&[
+                ("Constructor", 1),
+                ("Transfer", transfer),
+                ("Transfer", transfer),
+                ("Exports", EXPORTS_METHOD_NUM)
+            ]),
+            vec![
+                ("Constructor".to_string(), 1),
+                ("Transfer".to_string(), transfer),
+                ("Exports".to_string(), EXPORTS_METHOD_NUM),
+            ]
+        );
+    }
+}
@@ -0,0 +1,75 @@
+//! Interface introspection in the style of ERC-165
+//!
+//! An interface is a set of FRC-0042 method names and its ID is the XOR of their method numbers,
+//! so it doesn't depend on the order the methods are listed in. Actors that support introspection
+//! export a `SupportsInterface` method, which takes an [`InterfaceId`] and returns whether the
+//! actor implements that interface. Callers can then check an unknown actor before interacting
+//! with it.
+use fvm_shared::MethodNum;
+
+use crate::const_hash::method_number;
+
+/// Identifies a set of methods exported by an actor
+pub type InterfaceId = u32;
+
+/// Name of the introspection method
+pub const SUPPORTS_INTERFACE_METHOD_NAME: &str = "SupportsInterface";
+
+/// Method number of the introspection method
+pub const SUPPORTS_INTERFACE_METHOD_NUM: MethodNum = method_number(SUPPORTS_INTERFACE_METHOD_NAME);
+
+/// ID of the introspection interface itself, supported by every actor that answers queries
+pub const SUPPORTS_INTERFACE_ID: InterfaceId = interface_id(&[SUPPORTS_INTERFACE_METHOD_NAME]);
+
+/// An ID that no actor supports, as in ERC-165
+pub const INVALID_INTERFACE_ID: InterfaceId = 0xffffffff;
+
+/// Computes the ID of an interface from the names of its methods
+///
+/// Invalid method names fail to compile when used in a const context.
+pub const fn interface_id(method_names: &[&str]) -> InterfaceId {
+    let mut id = 0;
+    let mut i = 0;
+    while i < method_names.len() {
+        // method numbers are derived from 4 bytes of the digest so always fit in a u32
+        id ^= method_number(method_names[i]) as InterfaceId;
+        i += 1;
+    }
+    id
+}
+
+/// Answers a `SupportsInterface` query for an actor implementing `interfaces`
+///
+/// The introspection interface is always supported and [`INVALID_INTERFACE_ID`] never is.
+pub fn supports_interface(interfaces: &[InterfaceId], id: InterfaceId) -> bool {
+    id != INVALID_INTERFACE_ID && (id == SUPPORTS_INTERFACE_ID || interfaces.contains(&id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        interface_id, supports_interface, InterfaceId, INVALID_INTERFACE_ID, SUPPORTS_INTERFACE_ID,
+        SUPPORTS_INTERFACE_METHOD_NUM,
+    };
+    use crate::method_hash;
+
+    const GREETER: InterfaceId = interface_id(&["Greet", "Wave"]);
+
+    #[test]
+    fn computes_interface_ids() {
+        assert_eq!(GREETER, (method_hash!("Greet") ^ method_hash!("Wave")) as InterfaceId);
+        assert_eq!(interface_id(&["Wave", "Greet"]), GREETER);
+        assert_eq!(interface_id(&[]), 0);
+        assert_eq!(SUPPORTS_INTERFACE_ID as u64, SUPPORTS_INTERFACE_METHOD_NUM);
+        assert_eq!(SUPPORTS_INTERFACE_METHOD_NUM, method_hash!("SupportsInterface"));
+    }
+
+    #[test]
+    fn answers_queries() {
+        assert!(supports_interface(&[GREETER], GREETER));
+        assert!(supports_interface(&[GREETER], SUPPORTS_INTERFACE_ID));
+        assert!(supports_interface(&[], SUPPORTS_INTERFACE_ID));
+        assert!(!supports_interface(&[], GREETER));
+        assert!(!supports_interface(&[INVALID_INTERFACE_ID], INVALID_INTERFACE_ID));
+    }
+}
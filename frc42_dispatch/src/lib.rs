@@ -1,9 +1,21 @@
 pub use frc42_hasher as hasher;
+pub use frc42_hasher::const_hash;
+pub use frc42_hasher::convention;
 pub use frc42_hasher::hash;
+pub use frc42_macros::actor;
+#[doc(hidden)]
+pub use frc42_macros::check_method_names;
 pub use frc42_macros::method_hash;
 
+#[cfg(feature = "use_sdk")]
+pub mod entrypoint;
+pub mod exports;
+pub mod interface;
+pub mod manifest;
 pub mod match_method;
 pub mod message;
+pub mod registry;
+pub mod solidity;
 
 #[cfg(test)]
 mod tests {}
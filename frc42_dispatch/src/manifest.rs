@@ -0,0 +1,239 @@
+//! Machine-readable descriptions of the methods an actor exports
+//!
+//! A manifest lists each exported method's name, FRC-0042 method number and the names of its
+//! param and return types, so that explorers and wallets can decode calls to an actor. Manifests
+//! are generated by the [`actor`](crate::actor) attribute (as a [`HasActorManifest`] impl) or
+//! declared with [`actor_manifest!`](crate::actor_manifest), and serialize to CBOR or JSON.
+use fvm_shared::MethodNum;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::hash::{Hasher, MethodNameErr, MethodResolver};
+
+/// The methods exported by an actor
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ActorManifest {
+    /// Name of the actor
+    pub name: String,
+    /// Exported methods, in declaration order
+    pub methods: Vec<MethodManifest>,
+}
+
+/// A single exported method
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MethodManifest {
+    /// FRC-0042 method name
+    pub name: String,
+    /// Method number derived from the name
+    pub number: MethodNum,
+    /// Name of the param type, if the method takes params
+    pub params: Option<String>,
+    /// Name of the return type, if the method returns data
+    pub returns: Option<String>,
+}
+
+/// Implemented by the [`actor`](crate::actor) attribute for the actor type
+pub trait HasActorManifest {
+    /// Describes the methods exported by the actor
+    fn actor_manifest() -> ActorManifest;
+}
+
+#[derive(Error, Debug)]
+pub enum ManifestError {
+    #[error("invalid method name `{name}`: {source}")]
+    InvalidName { name: String, source: MethodNameErr },
+    #[error("method `{name}` has number {actual} but its name resolves to {expected}")]
+    NumberMismatch { name: String, expected: MethodNum, actual: MethodNum },
+    #[error("methods `{first}` and `{second}` both have method number {number}")]
+    DuplicateNumber { first: String, second: String, number: MethodNum },
+    #[error("manifest serialization error: {0}")]
+    Serialization(#[from] fvm_ipld_encoding::Error),
+}
+
+impl ActorManifest {
+    /// Creates an empty manifest for the named actor
+    pub fn new(name: &str) -> Self {
+        Self { name: name.into(), methods: Vec::new() }
+    }
+
+    /// Looks up an exported method by number
+    pub fn method(&self, number: MethodNum) -> Option<&MethodManifest> {
+        self.methods.iter().find(|m| m.number == number)
+    }
+
+    /// Checks that every method name is valid, matches its method number and that no two methods
+    /// share a number
+    pub fn validate<T: Hasher>(&self, resolver: &MethodResolver<T>) -> Result<(), ManifestError> {
+        for (i, method) in self.methods.iter().enumerate() {
+            let expected = resolver.method_number(&method.name).map_err(|source| {
+                ManifestError::InvalidName { name: method.name.clone(), source }
+            })?;
+            if expected != method.number {
+                return Err(ManifestError::NumberMismatch {
+                    name: method.name.clone(),
+                    expected,
+                    actual: method.number,
+                });
+            }
+            if let Some(first) = self.methods[..i].iter().find(|m| m.number == method.number) {
+                return Err(ManifestError::DuplicateNumber {
+                    first: first.name.clone(),
+                    second: method.name.clone(),
+                    number: method.number,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Serializes the manifest to CBOR
+    pub fn to_cbor(&self) -> Result<Vec<u8>, ManifestError> {
+        Ok(fvm_ipld_encoding::to_vec(self)?)
+    }
+
+    /// Deserializes a manifest from CBOR
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, ManifestError> {
+        Ok(fvm_ipld_encoding::from_slice(bytes)?)
+    }
+}
+
+/// Normalizes a type name rendered from a token stream (e.g. `Vec < u8 >` becomes `Vec<u8>`) so
+/// that manifests generated by macros and by source scanning agree
+pub fn normalize_type_name(name: &str) -> String {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::with_capacity(name.len());
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_whitespace() {
+            let prev = out.chars().last();
+            let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+            match (prev, next) {
+                (Some(p), Some(n)) if is_word(p) && is_word(*n) => out.push(' '),
+                (Some(','), Some(_)) | (Some(';'), Some(_)) => out.push(' '),
+                _ => {}
+            }
+        } else {
+            out.push(*c);
+        }
+    }
+    out
+}
+
+/// Declares an [`ActorManifest`](crate::manifest::ActorManifest) in the style of
+/// [`match_method!`](crate::match_method)
+///
+/// Each method is listed by name, followed by its param type (if any) in parentheses and an
+/// optional return type. Method numbers are computed at compile time.
+///
+/// ```
+/// use frc42_dispatch::actor_manifest;
+///
+/// let manifest = actor_manifest!("Greeter", {
+///     "Constructor"(),
+///     "Greet"(String) -> String,
+/// });
+/// assert_eq!(manifest.methods[1].params.as_deref(), Some("String"));
+/// ```
+#[macro_export]
+macro_rules! actor_manifest {
+    ($actor:literal, { $($name:literal ($($params:ty)?) $(-> $returns:ty)?),* $(,)? }) => {{
+        $crate::check_method_names!($($name),*);
+        $crate::manifest::ActorManifest {
+            name: ::std::string::String::from($actor),
+            methods: ::std::vec![$(
+                $crate::manifest::MethodManifest {
+                    name: ::std::string::String::from($name),
+                    number: $crate::method_hash!($name),
+                    params: ::std::option::Option::<&str>::None
+                        $(.or(::std::option::Option::Some(::std::stringify!($params))))?
+                        .map($crate::manifest::normalize_type_name),
+                    returns: ::std::option::Option::<&str>::None
+                        $(.or(::std::option::Option::Some(::std::stringify!($returns))))?
+                        .map($crate::manifest::normalize_type_name),
+                }
+            ),*],
+        }
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_type_name, ActorManifest, ManifestError, MethodManifest};
+    use crate::hash::{Hasher, MethodResolver};
+
+    /// Stands in for blake2b, validation only needs a consistent hasher
+    struct FakeHasher {}
+    impl Hasher for FakeHasher {
+        fn hash(&self, bytes: &[u8]) -> Vec<u8> {
+            bytes.to_vec()
+        }
+    }
+
+    fn method(name: &str, number: u64) -> MethodManifest {
+        MethodManifest { name: name.into(), number, params: None, returns: None }
+    }
+
+    #[test]
+    fn normalizes_type_names() {
+        assert_eq!(normalize_type_name("Vec < u8 >"), "Vec<u8>");
+        assert_eq!(
+            normalize_type_name("fvm_shared :: address :: Address"),
+            "fvm_shared::address::Address"
+        );
+        assert_eq!(normalize_type_name("(u64 , String)"), "(u64, String)");
+        assert_eq!(normalize_type_name("& 'static str"), "&'static str");
+        assert_eq!(normalize_type_name("[u8 ; 32]"), "[u8; 32]");
+    }
+
+    #[test]
+    fn declares_manifest() {
+        let manifest = crate::actor_manifest!("Token", {
+            "Constructor"(),
+            "Name"() -> String,
+            "BalanceOf"(Address) -> TokenAmount,
+            "Mint"(Vec<MintParams>),
+        });
+
+        assert_eq!(manifest.name, "Token");
+        assert_eq!(manifest.methods.len(), 4);
+        assert_eq!(manifest.methods[0], method("Constructor", 1));
+        assert_eq!(manifest.methods[1].number, crate::method_hash!("Name"));
+        assert_eq!(manifest.methods[2].params.as_deref(), Some("Address"));
+        assert_eq!(manifest.methods[2].returns.as_deref(), Some("TokenAmount"));
+        assert_eq!(manifest.methods[3].params.as_deref(), Some("Vec<MintParams>"));
+        assert_eq!(manifest.methods[3].returns, None);
+    }
+
+    #[test]
+    fn roundtrips_cbor() {
+        let manifest = crate::actor_manifest!("Greeter", {
+            "Constructor"(),
+            "Greet"(String) -> String,
+        });
+        let bytes = manifest.to_cbor().unwrap();
+        assert_eq!(ActorManifest::from_cbor(&bytes).unwrap(), manifest);
+    }
+
+    #[test]
+    fn validates_methods() {
+        let resolver = MethodResolver::new(FakeHasher {});
+        let number = resolver.method_number("Method").unwrap();
+
+        let mut manifest = ActorManifest::new("Test");
+        manifest.methods.push(method("Constructor", 1));
+        manifest.methods.push(method("Method", number));
+        manifest.validate(&resolver).unwrap();
+
+        manifest.methods.push(method("Method", number));
+        assert!(matches!(
+            manifest.validate(&resolver),
+            Err(ManifestError::DuplicateNumber { number: n, .. }) if n == number
+        ));
+
+        manifest.methods[2] = method("Other", number);
+        assert!(matches!(manifest.validate(&resolver), Err(ManifestError::NumberMismatch { .. })));
+
+        manifest.methods[2] = method("other", number);
+        assert!(matches!(manifest.validate(&resolver), Err(ManifestError::InvalidName { .. })));
+    }
+}
@@ -1,60 +1,292 @@
+/// Matches a method number against FRC-0042 method names
+///
+/// Each arm matches one or more names separated by `|`, for example to keep an old name working
+/// while callers migrate to a new one. Arms may have an `if` guard and may bind the method number
+/// with `num @ "Name"`. The last arm is usually `_` or an identifier binding any other method
+/// number. Names are hashed at compile time, and names colliding with another name or repeated in
+/// unguarded arms are a compile error.
+///
+/// Names are hashed with version 1 of the convention unless another is chosen with the same
+/// arguments as [`method_hash!`](crate::method_hash) before the arms, e.g.
+/// `match_method!(method, prefix = "acme|", { ... })`.
+///
+/// An `exports(methods) => ...` arm handles the standard `Exports` method, binding `methods` to
+/// the [`Exports`](crate::exports::Exports) listing every name matched by the other arms.
+///
+/// ```
+/// use frc42_dispatch::{match_method, method_hash};
+///
+/// let method_num = method_hash!("TransferFrom");
+/// let ret = match_method!(method_num, {
+///     "Constructor" => "constructor",
+///     "Transfer" | "TransferFrom" => "transfer",
+///     num @ "Mint" if num > 0 => "mint",
+///     _other => "unknown",
+/// });
+/// assert_eq!(ret, "transfer");
+/// ```
 #[macro_export]
 macro_rules! match_method {
     ($method:expr, {$($body:tt)*}) => {
-        match_method!{@match $method, {}, $($body)*}
+        match_method!{@match $method, [], {}, [], [], {}, $($body)*}
     };
-    (@match $method:expr, {$($body:tt)*}, $(,)*) => {
+    ($method:expr, $($key:ident = $value:literal),+, {$($body:tt)*}) => {
+        match_method!{@match $method, [$(, $key = $value)+], {}, [], [], {}, $($body)*}
+    };
+    (@match $method:expr, $conv:tt, {$($body:tt)*}, [$($names:tt)*], [$($exported:literal)*], {}, $(,)*) => {{
+        $crate::check_method_names!($conv $($names)*);
         match $method {
             $($body)*
         }
+    }};
+    // adds the exports arm once every exported name is known
+    (@match $method:expr, $conv:tt, {$($body:tt)*}, [$($names:tt)*], [$($exported:literal)*], {$list:ident $exports:tt}, $(,)*) => {{
+        $crate::check_method_names!($conv $($names)*);
+        match $method {
+            $crate::__match_method_hash!($conv "Exports") => {
+                let $list = $crate::exports::exports(&[$(($exported, $crate::__match_method_hash!($conv $exported))),*]);
+                $exports
+            }
+            $($body)*
+        }
+    }};
+    // matches named arms with comma
+    (@match $method:expr, $conv:tt, {$($body:tt)*}, [$($names:tt)*], [$($exported:literal)*], {$($exports:tt)*}, $($bind:ident @)? $p:literal $(| $ps:literal)* $(if $guard:expr)? => $e:expr, $($tail:tt)*) => {
+        match_method! {
+            @arm
+            $method,
+            $conv,
+            {$($body)*},
+            [$($names)*],
+            [$($exported)*],
+            {$($exports)*},
+            [$($bind)?],
+            [$p $($ps)*],
+            [$($guard)?],
+            $e,
+            $($tail)*
+        }
+    };
+    // matches named arms without comma
+    (@match $method:expr, $conv:tt, {$($body:tt)*}, [$($names:tt)*], [$($exported:literal)*], {$($exports:tt)*}, $($bind:ident @)? $p:literal $(| $ps:literal)* $(if $guard:expr)? => $e:block $($tail:tt)*) => {
+        match_method! {
+            @arm
+            $method,
+            $conv,
+            {$($body)*},
+            [$($names)*],
+            [$($exported)*],
+            {$($exports)*},
+            [$($bind)?],
+            [$p $($ps)*],
+            [$($guard)?],
+            $e,
+            $($tail)*
+        }
+    };
+    // matches a named arm without a trailing comma
+    (@match $method:expr, $conv:tt, {$($body:tt)*}, [$($names:tt)*], [$($exported:literal)*], {$($exports:tt)*}, $($bind:ident @)? $p:literal $(| $ps:literal)* $(if $guard:expr)? => $e:expr) => {
+        match_method! {
+            @arm
+            $method,
+            $conv,
+            {$($body)*},
+            [$($names)*],
+            [$($exported)*],
+            {$($exports)*},
+            [$($bind)?],
+            [$p $($ps)*],
+            [$($guard)?],
+            $e,
+        }
+    };
+    // matches the exports arm with a trailing comma
+    (@match $method:expr, $conv:tt, {$($body:tt)*}, [$($names:tt)*], [$($exported:literal)*], {}, exports($list:ident) => $e:expr, $($tail:tt)*) => {
+        match_method! {
+            @match
+            $method,
+            $conv,
+            {$($body)*},
+            [$($names)* exports "Exports"],
+            [$($exported)* "Exports"],
+            {$list $e},
+            $($tail)*
+        }
+    };
+    // matches the exports arm with a block and no comma
+    (@match $method:expr, $conv:tt, {$($body:tt)*}, [$($names:tt)*], [$($exported:literal)*], {}, exports($list:ident) => $e:block $($tail:tt)*) => {
+        match_method! {
+            @match
+            $method,
+            $conv,
+            {$($body)*},
+            [$($names)* exports "Exports"],
+            [$($exported)* "Exports"],
+            {$list $e},
+            $($tail)*
+        }
+    };
+    // matches the exports arm without a trailing comma
+    (@match $method:expr, $conv:tt, {$($body:tt)*}, [$($names:tt)*], [$($exported:literal)*], {}, exports($list:ident) => $e:expr) => {
+        match_method! {
+            @match
+            $method,
+            $conv,
+            {$($body)*},
+            [$($names)* exports "Exports"],
+            [$($exported)* "Exports"],
+            {$list $e},
+        }
+    };
+    // matches _ or a binding with a trailing comma
+    (@match $method:expr, $conv:tt, {$($body:tt)*}, [$($names:tt)*], [$($exported:literal)*], {$($exports:tt)*}, $catch:tt $(if $guard:expr)? => $e:expr, $($tail:tt)*) => {
+        match_method! {
+            @match
+            $method,
+            $conv,
+            {
+                $($body)*
+                $catch $(if $guard)? => $e,
+            },
+            [$($names)*],
+            [$($exported)*],
+            {$($exports)*},
+            $($tail)*
+        }
+    };
+    // matches _ or a binding with a block and no comma
+    (@match $method:expr, $conv:tt, {$($body:tt)*}, [$($names:tt)*], [$($exported:literal)*], {$($exports:tt)*}, $catch:tt $(if $guard:expr)? => $e:block $($tail:tt)*) => {
+        match_method! {
+            @match
+            $method,
+            $conv,
+            {
+                $($body)*
+                $catch $(if $guard)? => $e,
+            },
+            [$($names)*],
+            [$($exported)*],
+            {$($exports)*},
+            $($tail)*
+        }
+    };
+    // matches _ or a binding without a trailing comma (common if it's the last item)
+    (@match $method:expr, $conv:tt, {$($body:tt)*}, [$($names:tt)*], [$($exported:literal)*], {$($exports:tt)*}, $catch:tt $(if $guard:expr)? => $e:expr) => {
+        match_method! {
+            @match
+            $method,
+            $conv,
+            {
+                $($body)*
+                $catch $(if $guard)? => $e,
+            },
+            [$($names)*],
+            [$($exported)*],
+            {$($exports)*},
+        }
     };
-    // matches block with comma
-    (@match $method:expr, {$($body:tt)*}, $p:literal => $e:expr, $($tail:tt)*) => {
+    // names are checked for duplicates and collisions, but guarded arms may legitimately repeat
+    // a name so their names are marked with `if`
+    (@arm $method:expr, $conv:tt, {$($body:tt)*}, [$($names:tt)*], [$($exported:literal)*], {$($exports:tt)*}, [], [$($p:literal)+], [], $e:tt, $($tail:tt)*) => {
         match_method! {
             @match
             $method,
+            $conv,
             {
                 $($body)*
-                $crate::method_hash!($p) => $e,
+                $($crate::__match_method_hash!($conv $p))|+ => $e,
             },
+            [$($names)* $($p)+],
+            [$($exported)* $($p)+],
+            {$($exports)*},
             $($tail)*
         }
     };
-    // matches block without comma
-    (@match $method:expr, {$($body:tt)*}, $p:literal => $e:block $($tail:tt)*) => {
+    (@arm $method:expr, $conv:tt, {$($body:tt)*}, [$($names:tt)*], [$($exported:literal)*], {$($exports:tt)*}, [], [$($p:literal)+], [$guard:tt], $e:tt, $($tail:tt)*) => {
         match_method! {
             @match
             $method,
+            $conv,
             {
                 $($body)*
-                $crate::method_hash!($p) => $e,
+                $($crate::__match_method_hash!($conv $p))|+ if $guard => $e,
             },
+            [$($names)* $(if $p)+],
+            [$($exported)* $($p)+],
+            {$($exports)*},
             $($tail)*
         }
     };
-    // matches _ with a trailing comma
-    (@match $method:expr, {$($body:tt)*}, _ => $e:expr, $($tail:tt)*) => {
+    // a single bound name needs no parentheses around the pattern
+    (@arm $method:expr, $conv:tt, {$($body:tt)*}, [$($names:tt)*], [$($exported:literal)*], {$($exports:tt)*}, [$bind:ident], [$p:literal], [], $e:tt, $($tail:tt)*) => {
         match_method! {
             @match
             $method,
+            $conv,
             {
                 $($body)*
-                _ => $e,
+                $bind @ $crate::__match_method_hash!($conv $p) => $e,
             },
+            [$($names)* $p],
+            [$($exported)* $p],
+            {$($exports)*},
             $($tail)*
         }
     };
-    // matches _ without a trailing comma (common if it's the last item)
-    (@match $method:expr, {$($body:tt)*}, _ => $e:expr) => {
+    (@arm $method:expr, $conv:tt, {$($body:tt)*}, [$($names:tt)*], [$($exported:literal)*], {$($exports:tt)*}, [$bind:ident], [$p:literal], [$guard:tt], $e:tt, $($tail:tt)*) => {
         match_method! {
             @match
             $method,
+            $conv,
             {
                 $($body)*
-                _ => $e,
+                $bind @ $crate::__match_method_hash!($conv $p) if $guard => $e,
             },
+            [$($names)* if $p],
+            [$($exported)* $p],
+            {$($exports)*},
+            $($tail)*
         }
     };
+    (@arm $method:expr, $conv:tt, {$($body:tt)*}, [$($names:tt)*], [$($exported:literal)*], {$($exports:tt)*}, [$bind:ident], [$($p:literal)+], [], $e:tt, $($tail:tt)*) => {
+        match_method! {
+            @match
+            $method,
+            $conv,
+            {
+                $($body)*
+                $bind @ ($($crate::__match_method_hash!($conv $p))|+) => $e,
+            },
+            [$($names)* $($p)+],
+            [$($exported)* $($p)+],
+            {$($exports)*},
+            $($tail)*
+        }
+    };
+    (@arm $method:expr, $conv:tt, {$($body:tt)*}, [$($names:tt)*], [$($exported:literal)*], {$($exports:tt)*}, [$bind:ident], [$($p:literal)+], [$guard:tt], $e:tt, $($tail:tt)*) => {
+        match_method! {
+            @match
+            $method,
+            $conv,
+            {
+                $($body)*
+                $bind @ ($($crate::__match_method_hash!($conv $p))|+) if $guard => $e,
+            },
+            [$($names)* $(if $p)+],
+            [$($exported)* $($p)+],
+            {$($exports)*},
+            $($tail)*
+        }
+    };
+}
+
+/// Hashes a name passed to `match_method!` with the convention arguments it was given
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __match_method_hash {
+    ([$($convention:tt)*] $name:literal) => {
+        $crate::method_hash!($name $($convention)*)
+    };
 }
 
 #[cfg(test)]
@@ -108,4 +340,116 @@ mod tests {
 
         assert_eq!(ret, Some(2));
     }
+
+    #[test]
+    fn handle_aliases() {
+        let method = |method_num: u64| {
+            match_method!(method_num, {
+                "Constructor" => Some(1),
+                "Transfer" | "TransferFrom" => Some(2),
+                _ => None,
+            })
+        };
+
+        assert_eq!(method(crate::method_hash!("Transfer")), Some(2));
+        assert_eq!(method(crate::method_hash!("TransferFrom")), Some(2));
+        assert_eq!(method(crate::method_hash!("Burn")), None);
+    }
+
+    #[test]
+    fn handle_guards() {
+        let method = |method_num: u64, migrated: bool| {
+            match_method!(method_num, {
+                "Transfer" if migrated => Some(1),
+                "Transfer" => Some(2),
+                "Burn" | "BurnFrom" if !migrated => {
+                    Some(3)
+                }
+                _ => None,
+            })
+        };
+
+        assert_eq!(method(crate::method_hash!("Transfer"), true), Some(1));
+        assert_eq!(method(crate::method_hash!("Transfer"), false), Some(2));
+        assert_eq!(method(crate::method_hash!("BurnFrom"), false), Some(3));
+        assert_eq!(method(crate::method_hash!("BurnFrom"), true), None);
+    }
+
+    #[test]
+    fn handle_bindings() {
+        let method = |method_num: u64| {
+            match_method!(method_num, {
+                num @ "Constructor" => num,
+                num @ "Transfer" | "TransferFrom" if num % 2 == 0 => num + 1,
+                num @ "Transfer" | "TransferFrom" => num,
+                other if other < 1 << 24 => 0,
+                other => other
+            })
+        };
+
+        assert_eq!(method(1), 1);
+        let transfer = crate::method_hash!("Transfer");
+        assert_eq!(method(transfer), transfer + (transfer + 1) % 2);
+        assert_eq!(method(12345), 0);
+        assert_eq!(method(crate::method_hash!("Burn")), crate::method_hash!("Burn"));
+    }
+
+    #[test]
+    fn handle_exports() {
+        let method = |method_num: u64| {
+            match_method!(method_num, {
+                "Constructor" => None,
+                "Transfer" | "TransferFrom" => None,
+                "Burn" if method_num > 0 => None,
+                exports(methods) => Some(methods),
+                _ => None,
+            })
+        };
+
+        let exports = method(crate::exports::EXPORTS_METHOD_NUM).unwrap();
+        let names = exports.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["Constructor", "Transfer", "TransferFrom", "Burn", "Exports"]);
+        assert_eq!(exports[1].1, crate::method_hash!("Transfer"));
+        assert_eq!(exports[4].1, crate::method_hash!("Exports"));
+        assert_eq!(method(crate::method_hash!("Transfer")), None);
+    }
+
+    #[test]
+    fn handle_conventions() {
+        let acme = |method_num: u64| {
+            match_method!(method_num, prefix = "acme|", {
+                "Constructor" => Some(1),
+                "Transfer" => Some(2),
+                exports(methods) => Some(methods.len()),
+                _ => None,
+            })
+        };
+
+        assert_eq!(acme(1), Some(1));
+        assert_eq!(acme(crate::method_hash!("Transfer", prefix = "acme|")), Some(2));
+        assert_eq!(acme(crate::method_hash!("Transfer")), None);
+        assert_eq!(acme(crate::method_hash!("Exports", prefix = "acme|")), Some(3));
+        assert_eq!(acme(crate::exports::EXPORTS_METHOD_NUM), None);
+
+        let v1 = |method_num: u64| {
+            match_method!(method_num, version = 1, {
+                "Transfer" => Some(2),
+                _ => None,
+            })
+        };
+        assert_eq!(v1(crate::method_hash!("Transfer")), Some(2));
+    }
+
+    #[test]
+    fn reject_colliding_names() {
+        let t = trybuild::TestCases::new();
+        // NOTE: these need to live in a separate directory under `tests`
+        // otherwise cargo tries to build them every time
+        t.compile_fail("tests/match_method/colliding-arms.rs");
+        t.compile_fail("tests/match_method/colliding-guarded-arms.rs");
+        t.compile_fail("tests/match_method/duplicate-arms.rs");
+        t.compile_fail("tests/match_method/duplicate-alias.rs");
+        t.compile_fail("tests/match_method/duplicate-exports.rs");
+        t.compile_fail("tests/match_method/guarded-exports.rs");
+    }
 }
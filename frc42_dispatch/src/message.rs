@@ -1,11 +1,74 @@
 use fvm_ipld_encoding::ipld_block::IpldBlock;
 #[cfg(feature = "use_sdk")]
 use fvm_sdk::send;
+use fvm_shared::sys::SendFlags;
 use fvm_shared::{address::Address, econ::TokenAmount, error::ErrorNumber, Response};
 use thiserror::Error;
 
 use crate::hash::{Hasher, MethodNameErr, MethodResolver};
 
+/// Options for sending a message, beyond its recipient, method, params and value
+///
+/// ```
+/// use frc42_dispatch::message::SendOptions;
+///
+/// // a balance query that can't change state, with bounded gas
+/// let options = SendOptions::new().read_only().with_gas_limit(10_000_000);
+/// assert!(options.is_read_only());
+/// assert_eq!(options.gas_limit(), Some(10_000_000));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SendOptions {
+    gas_limit: Option<u64>,
+    flags: SendFlags,
+}
+
+impl Default for SendOptions {
+    fn default() -> Self {
+        Self { gas_limit: None, flags: SendFlags::empty() }
+    }
+}
+
+impl SendOptions {
+    /// Options giving the receiving actor all available gas, with no flags set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the gas available to the receiving actor
+    pub fn with_gas_limit(mut self, gas_limit: u64) -> Self {
+        self.gas_limit = Some(gas_limit);
+        self
+    }
+
+    /// Sends the message read-only, so neither the receiving actor nor the actors it calls can
+    /// change state or transfer funds
+    pub fn read_only(self) -> Self {
+        self.with_flags(SendFlags::READ_ONLY)
+    }
+
+    /// Sets flags on the message, in addition to those already set
+    pub fn with_flags(mut self, flags: SendFlags) -> Self {
+        self.flags |= flags;
+        self
+    }
+
+    /// The gas limit of the message, or None to give it all available gas
+    pub fn gas_limit(&self) -> Option<u64> {
+        self.gas_limit
+    }
+
+    /// The flags set on the message
+    pub fn flags(&self) -> SendFlags {
+        self.flags
+    }
+
+    /// Whether the message is sent read-only
+    pub fn is_read_only(&self) -> bool {
+        self.flags.contains(SendFlags::READ_ONLY)
+    }
+}
+
 /// Utility to invoke standard methods on deployed actors
 #[derive(Default)]
 pub struct MethodMessenger<T: Hasher> {
@@ -35,9 +98,22 @@ impl<T: Hasher> MethodMessenger<T> {
         method: &str,
         params: Option<IpldBlock>,
         value: TokenAmount,
+    ) -> Result<Response, MethodMessengerError> {
+        self.call_method_with_options(to, method, params, value, SendOptions::default())
+    }
+
+    /// Calls a method (by name) on a specified actor, sending the message with the given options
+    #[cfg(feature = "use_sdk")]
+    pub fn call_method_with_options(
+        &self,
+        to: &Address,
+        method: &str,
+        params: Option<IpldBlock>,
+        value: TokenAmount,
+        options: SendOptions,
     ) -> Result<Response, MethodMessengerError> {
         let method = self.method_resolver.method_number(method)?;
-        send::send(to, method, params, value, None, fvm_shared::sys::SendFlags::empty())
+        send::send(to, method, params, value, options.gas_limit(), options.flags())
             .map_err(MethodMessengerError::from)
     }
 
@@ -0,0 +1,337 @@
+//! Dispatch tables assembled at runtime
+//!
+//! [`match_method!`](crate::match_method) and [`actor`](crate::actor) fix an actor's methods at
+//! compile time. Actors composed from several modules (e.g. a token, access control and upgrade
+//! logic) can instead have each module register its methods with a [`MethodRegistry`], which
+//! resolves the names, rejects collisions as they are registered and dispatches incoming messages
+//! to the matching handler.
+use std::fmt::Display;
+
+use fvm_ipld_encoding::de::DeserializeOwned;
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_ipld_encoding::ser::Serialize;
+use fvm_shared::error::ExitCode;
+use fvm_shared::MethodNum;
+use serde::de::value::{Error as ValueError, UnitDeserializer};
+use thiserror::Error;
+
+use crate::convention::Convention;
+use crate::hash::{Hasher, MethodNameErr, MethodResolver};
+
+/// A method handler, taking the raw params of a message and returning its raw return data
+pub type Handler<'a> =
+    Box<dyn FnMut(Option<IpldBlock>) -> Result<Option<IpldBlock>, RegistryError> + 'a>;
+
+#[derive(Error, Debug)]
+pub enum RegistryError {
+    #[error("invalid method name `{name}`: {source}")]
+    InvalidName { name: String, source: MethodNameErr },
+    #[error("method `{name}` is already registered")]
+    DuplicateName { name: String },
+    #[error("methods `{existing}` and `{name}` both resolve to method number {number}")]
+    Collision { existing: String, name: String, number: MethodNum },
+    #[error("unrecognized method number {0}")]
+    Unhandled(MethodNum),
+    #[error("method `{name}` expects parameters")]
+    MissingParams { name: String },
+    #[error("method `{name}` failed: {message}")]
+    Handler { name: String, exit_code: ExitCode, message: String },
+    #[error("ipld serialization error: {0}")]
+    Serialization(#[from] fvm_ipld_encoding::Error),
+}
+
+impl From<&RegistryError> for ExitCode {
+    fn from(error: &RegistryError) -> Self {
+        match error {
+            // registration errors are bugs in how the actor was assembled
+            RegistryError::InvalidName { .. }
+            | RegistryError::DuplicateName { .. }
+            | RegistryError::Collision { .. } => ExitCode::USR_ILLEGAL_STATE,
+            RegistryError::Unhandled(_) => ExitCode::USR_UNHANDLED_MESSAGE,
+            RegistryError::MissingParams { .. } => ExitCode::USR_ILLEGAL_ARGUMENT,
+            RegistryError::Handler { exit_code, .. } => *exit_code,
+            RegistryError::Serialization(_) => ExitCode::USR_SERIALIZATION,
+        }
+    }
+}
+
+struct RegisteredMethod<'a> {
+    name: String,
+    number: MethodNum,
+    handler: Handler<'a>,
+}
+
+/// Maps method numbers to handlers registered by name
+///
+/// ```
+/// use frc42_dispatch::hash::Hasher;
+/// use frc42_dispatch::registry::{MethodRegistry, RegistryError};
+/// # struct FakeHasher {}
+/// # impl Hasher for FakeHasher {
+/// #     fn hash(&self, bytes: &[u8]) -> Vec<u8> {
+/// #         bytes.to_vec()
+/// #     }
+/// # }
+///
+/// let mut registry = MethodRegistry::new(FakeHasher {});
+/// let greet = registry
+///     .register_typed("Greet", |name: String| Ok::<_, RegistryError>(format!("Hello, {name}")))?;
+///
+/// let params = fvm_ipld_encoding::ipld_block::IpldBlock::serialize_cbor(&"Alice")?;
+/// let ret = registry.dispatch(greet, params)?.unwrap();
+/// assert_eq!(ret.deserialize::<String>()?, "Hello, Alice");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct MethodRegistry<'a, T: Hasher> {
+    resolver: MethodResolver<T>,
+    methods: Vec<RegisteredMethod<'a>>,
+}
+
+impl<T: Hasher + Default> Default for MethodRegistry<'_, T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<'a, T: Hasher> MethodRegistry<'a, T> {
+    /// Creates an empty registry resolving method names with the given hasher
+    pub fn new(hasher: T) -> Self {
+        Self { resolver: MethodResolver::new(hasher), methods: Vec::new() }
+    }
+
+    /// Creates an empty registry resolving method names with a specific hashing convention
+    pub fn with_convention(hasher: T, convention: Convention) -> Self {
+        Self { resolver: MethodResolver::with_convention(hasher, convention), methods: Vec::new() }
+    }
+
+    /// Registers a handler for a method name, returning its method number
+    ///
+    /// Fails if the name is invalid, already registered or resolves to the same method number as
+    /// another registered name.
+    pub fn register<F>(&mut self, name: &str, handler: F) -> Result<MethodNum, RegistryError>
+    where
+        F: FnMut(Option<IpldBlock>) -> Result<Option<IpldBlock>, RegistryError> + 'a,
+    {
+        let number = self
+            .resolver
+            .method_number(name)
+            .map_err(|source| RegistryError::InvalidName { name: name.into(), source })?;
+        if let Some(existing) = self.methods.iter().find(|m| m.number == number) {
+            return Err(if existing.name == name {
+                RegistryError::DuplicateName { name: name.into() }
+            } else {
+                RegistryError::Collision {
+                    existing: existing.name.clone(),
+                    name: name.into(),
+                    number,
+                }
+            });
+        }
+        self.methods.push(RegisteredMethod {
+            name: name.into(),
+            number,
+            handler: Box::new(handler),
+        });
+        Ok(number)
+    }
+
+    /// Registers a handler taking deserialized params and returning a serializable value
+    ///
+    /// Missing params are read as unit, so handlers taking `()` or an `Option` can be called without
+    /// params. Errors returned by the handler are reported as [`RegistryError::Handler`] with the
+    /// exit code they convert into.
+    pub fn register_typed<P, R, E, F>(
+        &mut self,
+        name: &str,
+        mut handler: F,
+    ) -> Result<MethodNum, RegistryError>
+    where
+        P: DeserializeOwned,
+        R: Serialize,
+        E: Display,
+        for<'e> ExitCode: From<&'e E>,
+        F: FnMut(P) -> Result<R, E> + 'a,
+    {
+        let method_name = name.to_string();
+        self.register(name, move |params| {
+            let params = match params {
+                Some(params) => params.deserialize()?,
+                None => P::deserialize(UnitDeserializer::<ValueError>::new())
+                    .map_err(|_| RegistryError::MissingParams { name: method_name.clone() })?,
+            };
+            let ret = handler(params).map_err(|e| RegistryError::Handler {
+                name: method_name.clone(),
+                exit_code: ExitCode::from(&e),
+                message: e.to_string(),
+            })?;
+            Ok(IpldBlock::serialize_cbor(&ret)?)
+        })
+    }
+
+    /// Calls the handler registered for a method number
+    pub fn dispatch(
+        &mut self,
+        method: MethodNum,
+        params: Option<IpldBlock>,
+    ) -> Result<Option<IpldBlock>, RegistryError> {
+        let registered = self
+            .methods
+            .iter_mut()
+            .find(|m| m.number == method)
+            .ok_or(RegistryError::Unhandled(method))?;
+        (registered.handler)(params)
+    }
+
+    /// Returns whether a handler is registered for a method number
+    pub fn contains(&self, method: MethodNum) -> bool {
+        self.methods.iter().any(|m| m.number == method)
+    }
+
+    /// Returns the name registered for a method number
+    pub fn method_name(&self, method: MethodNum) -> Option<&str> {
+        self.methods.iter().find(|m| m.number == method).map(|m| m.name.as_str())
+    }
+
+    /// Lists the registered names and method numbers, in registration order
+    pub fn methods(&self) -> impl Iterator<Item = (&str, MethodNum)> {
+        self.methods.iter().map(|m| (m.name.as_str(), m.number))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use fvm_ipld_encoding::ipld_block::IpldBlock;
+    use fvm_shared::error::ExitCode;
+    use thiserror::Error;
+
+    use super::{MethodRegistry, RegistryError};
+    use crate::convention::Convention;
+    use crate::hash::{Hasher, MethodResolver};
+
+    #[derive(Error, Debug)]
+    #[error("insufficient funds")]
+    struct InsufficientFunds;
+
+    impl From<&InsufficientFunds> for ExitCode {
+        fn from(_: &InsufficientFunds) -> Self {
+            ExitCode::USR_INSUFFICIENT_FUNDS
+        }
+    }
+
+    /// Stands in for blake2b, so method numbers only depend on the first two letters of a name
+    #[derive(Default)]
+    struct FakeHasher {}
+    impl Hasher for FakeHasher {
+        fn hash(&self, bytes: &[u8]) -> Vec<u8> {
+            bytes.to_vec()
+        }
+    }
+
+    #[test]
+    fn dispatches_to_modules() {
+        let counter = RefCell::new(0u64);
+        let mut registry = MethodRegistry::<FakeHasher>::default();
+
+        // two modules registering their own methods
+        let increment = registry
+            .register_typed("Increment", |by: u64| {
+                *counter.borrow_mut() += by;
+                Ok::<_, RegistryError>(*counter.borrow())
+            })
+            .unwrap();
+        let reset = registry
+            .register("Reset", |_| {
+                *counter.borrow_mut() = 0;
+                Ok(None)
+            })
+            .unwrap();
+
+        let ret = registry.dispatch(increment, IpldBlock::serialize_cbor(&5u64).unwrap()).unwrap();
+        assert_eq!(ret.unwrap().deserialize::<u64>().unwrap(), 5);
+        assert_eq!(registry.dispatch(reset, None).unwrap(), None);
+
+        assert!(registry.contains(reset));
+        assert_eq!(registry.method_name(increment), Some("Increment"));
+        assert_eq!(
+            registry.methods().collect::<Vec<_>>(),
+            [("Increment", increment), ("Reset", reset)]
+        );
+        drop(registry);
+        assert_eq!(counter.into_inner(), 0);
+    }
+
+    #[test]
+    fn registers_with_a_convention() {
+        let private = Convention::V1.with_prefix("acme|");
+        let mut registry = MethodRegistry::with_convention(FakeHasher {}, private.clone());
+        let transfer = registry.register("Transfer", |_| Ok(None)).unwrap();
+
+        let resolver = MethodResolver::with_convention(FakeHasher {}, private);
+        assert_eq!(transfer, resolver.method_number("Transfer").unwrap());
+        assert_ne!(transfer, MethodResolver::new(FakeHasher {}).method_number("Transfer").unwrap());
+    }
+
+    #[test]
+    fn rejects_bad_registrations() {
+        let mut registry = MethodRegistry::new(FakeHasher {});
+        let number = registry.register("MethodA", |_| Ok(None)).unwrap();
+
+        assert!(matches!(
+            registry.register("MethodA", |_| Ok(None)),
+            Err(RegistryError::DuplicateName { .. })
+        ));
+        assert!(matches!(
+            registry.register("MethodB", |_| Ok(None)),
+            Err(RegistryError::Collision { existing, number: n, .. }) if existing == "MethodA" && n == number
+        ));
+        let err = registry.register("methodC", |_| Ok(None)).unwrap_err();
+        assert!(matches!(err, RegistryError::InvalidName { .. }));
+        assert_eq!(ExitCode::from(&err), ExitCode::USR_ILLEGAL_STATE);
+    }
+
+    #[test]
+    fn reports_dispatch_errors() {
+        let mut registry = MethodRegistry::new(FakeHasher {});
+        let number = registry
+            .register_typed("Withdraw", |amount: u64| {
+                if amount > 10 {
+                    Err(InsufficientFunds)
+                } else {
+                    Ok(amount)
+                }
+            })
+            .unwrap();
+
+        let err = registry.dispatch(number + 1, None).unwrap_err();
+        assert_eq!(ExitCode::from(&err), ExitCode::USR_UNHANDLED_MESSAGE);
+        let err = registry.dispatch(number, None).unwrap_err();
+        assert_eq!(ExitCode::from(&err), ExitCode::USR_ILLEGAL_ARGUMENT);
+        let params = IpldBlock::serialize_cbor(&"ten").unwrap();
+        let err = registry.dispatch(number, params).unwrap_err();
+        assert_eq!(ExitCode::from(&err), ExitCode::USR_SERIALIZATION);
+        let params = IpldBlock::serialize_cbor(&11u64).unwrap();
+        let err = registry.dispatch(number, params).unwrap_err();
+        assert_eq!(ExitCode::from(&err), ExitCode::USR_INSUFFICIENT_FUNDS);
+    }
+
+    #[test]
+    fn reads_missing_params_as_unit() {
+        let mut registry = MethodRegistry::new(FakeHasher {});
+        let ping = registry.register_typed("Ping", |()| Ok::<_, RegistryError>("pong")).unwrap();
+        let limit = registry
+            .register_typed("Limit", |max: Option<u64>| Ok::<_, RegistryError>(max.unwrap_or(10)))
+            .unwrap();
+
+        let ret = registry.dispatch(ping, None).unwrap();
+        assert_eq!(ret.unwrap().deserialize::<String>().unwrap(), "pong");
+        let ret = registry.dispatch(limit, None).unwrap();
+        assert_eq!(ret.unwrap().deserialize::<u64>().unwrap(), 10);
+        let params = IpldBlock::serialize_cbor(&Some(3u64)).unwrap();
+        let ret = registry.dispatch(limit, params).unwrap();
+        assert_eq!(ret.unwrap().deserialize::<u64>().unwrap(), 3);
+        let params = IpldBlock::serialize_cbor(&()).unwrap();
+        assert!(registry.dispatch(ping, params).is_ok());
+    }
+}
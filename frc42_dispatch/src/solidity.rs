@@ -0,0 +1,774 @@
+//! Bridge from Solidity calls to FRC-0042 methods
+//!
+//! FEVM contracts call other actors by sending `InvokeEVM` with ABI-encoded calldata: a 4-byte
+//! selector derived from the Solidity function signature followed by the encoded arguments. A
+//! native actor can opt in to being called from Solidity by routing selectors to its FRC-0042
+//! methods with a [`SolidityBridge`], which decodes the arguments and re-encodes them as the CBOR
+//! params of the native method.
+//!
+//! Arguments map to CBOR as follows:
+//!
+//! - `address` becomes an [`Address`], with masked ID addresses (`0xff0000000000000000000000` and
+//!   an 8 byte ID) mapped to ID addresses and anything else to an f410 (EVM) delegated address
+//! - `uint8` to `uint64` become integers and `uint256` a [`TokenAmount`] in attoFIL
+//! - `bool`, `bytes` and `string` map to their CBOR counterparts and `T[]` to a CBOR array
+//!
+//! Return values are not translated, so a Solidity caller receives the CBOR return of the native
+//! method.
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_shared::address::{Address, Payload};
+use fvm_shared::bigint::{BigInt, Sign};
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use fvm_shared::MethodNum;
+use serde::{Serialize, Serializer};
+use thiserror::Error;
+
+use crate::const_hash::method_number;
+
+/// The first four bytes of the keccak-256 hash of a Solidity function signature
+pub type Selector = [u8; 4];
+
+/// Method number of messages sent by FEVM contracts to other actors
+pub const INVOKE_EVM_METHOD_NUM: MethodNum = method_number("InvokeEVM");
+
+/// Address namespace of the Ethereum Address Manager, used for f410 addresses
+pub const EAM_NAMESPACE: u64 = 10;
+
+const WORD: usize = 32;
+
+#[derive(Error, Debug)]
+pub enum BridgeError {
+    #[error("invalid Solidity signature `{0}`")]
+    InvalidSignature(String),
+    #[error("unsupported Solidity type `{0}`")]
+    UnsupportedType(String),
+    #[error("selector {} is already routed to `{1}`", hex(.0))]
+    DuplicateSelector(Selector, String),
+    #[error("no method is routed for selector {}", hex(.0))]
+    UnknownSelector(Selector),
+    #[error("malformed calldata: {0}")]
+    Calldata(String),
+    #[error("invalid argument: {0}")]
+    InvalidArgument(String),
+    #[error("params serialization error: {0}")]
+    Serialization(#[from] fvm_ipld_encoding::Error),
+}
+
+impl From<&BridgeError> for ExitCode {
+    fn from(error: &BridgeError) -> Self {
+        match error {
+            BridgeError::InvalidSignature(_)
+            | BridgeError::UnsupportedType(_)
+            | BridgeError::DuplicateSelector(..) => ExitCode::USR_ILLEGAL_STATE,
+            BridgeError::UnknownSelector(_) => ExitCode::USR_UNHANDLED_MESSAGE,
+            BridgeError::Calldata(_) | BridgeError::Serialization(_) => ExitCode::USR_SERIALIZATION,
+            BridgeError::InvalidArgument(_) => ExitCode::USR_ILLEGAL_ARGUMENT,
+        }
+    }
+}
+
+fn hex(selector: &Selector) -> String {
+    format!("0x{:08x}", u32::from_be_bytes(*selector))
+}
+
+/// A Solidity type that can be bridged to CBOR
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AbiType {
+    Address,
+    Bool,
+    /// An unsigned integer of at most 64 bits
+    Uint(u16),
+    Uint256,
+    Bytes,
+    String,
+    Array(Box<AbiType>),
+}
+
+impl AbiType {
+    fn is_dynamic(&self) -> bool {
+        matches!(self, AbiType::Bytes | AbiType::String | AbiType::Array(_))
+    }
+}
+
+impl FromStr for AbiType {
+    type Err = BridgeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(inner) = s.strip_suffix("[]") {
+            return Ok(AbiType::Array(Box::new(inner.parse()?)));
+        }
+        match s {
+            "address" => Ok(AbiType::Address),
+            "bool" => Ok(AbiType::Bool),
+            "uint8" => Ok(AbiType::Uint(8)),
+            "uint16" => Ok(AbiType::Uint(16)),
+            "uint32" => Ok(AbiType::Uint(32)),
+            "uint64" => Ok(AbiType::Uint(64)),
+            "uint256" => Ok(AbiType::Uint256),
+            "bytes" => Ok(AbiType::Bytes),
+            "string" => Ok(AbiType::String),
+            _ => Err(BridgeError::UnsupportedType(s.into())),
+        }
+    }
+}
+
+impl Display for AbiType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AbiType::Address => write!(f, "address"),
+            AbiType::Bool => write!(f, "bool"),
+            AbiType::Uint(bits) => write!(f, "uint{bits}"),
+            AbiType::Uint256 => write!(f, "uint256"),
+            AbiType::Bytes => write!(f, "bytes"),
+            AbiType::String => write!(f, "string"),
+            AbiType::Array(inner) => write!(f, "{inner}[]"),
+        }
+    }
+}
+
+/// A decoded Solidity argument, which serializes to the CBOR expected by native actors
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AbiValue {
+    Address(Address),
+    Bool(bool),
+    Uint(u64),
+    Uint256(TokenAmount),
+    Bytes(Vec<u8>),
+    String(String),
+    Array(Vec<AbiValue>),
+}
+
+impl Serialize for AbiValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            AbiValue::Address(address) => address.serialize(serializer),
+            AbiValue::Bool(value) => serializer.serialize_bool(*value),
+            AbiValue::Uint(value) => serializer.serialize_u64(*value),
+            AbiValue::Uint256(amount) => amount.serialize(serializer),
+            AbiValue::Bytes(bytes) => serializer.serialize_bytes(bytes),
+            AbiValue::String(string) => serializer.serialize_str(string),
+            AbiValue::Array(values) => values.serialize(serializer),
+        }
+    }
+}
+
+/// How decoded arguments are passed to the native method
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParamsLayout {
+    /// As a CBOR array, matching a params struct serialized as a tuple
+    Tuple,
+    /// A single argument passed on its own, e.g. an `Address`
+    Unwrapped,
+}
+
+/// A Solidity function routed to an FRC-0042 method
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SolidityMethod {
+    /// Canonical Solidity signature, e.g. `transfer(address,uint256,bytes)`
+    pub signature: String,
+    pub selector: Selector,
+    /// FRC-0042 method number the call is routed to
+    pub method: MethodNum,
+    pub inputs: Vec<AbiType>,
+    pub layout: ParamsLayout,
+}
+
+impl SolidityMethod {
+    pub fn new(
+        signature: &str,
+        method: MethodNum,
+        layout: ParamsLayout,
+    ) -> Result<Self, BridgeError> {
+        let inputs = parse_signature(signature)?;
+        if layout == ParamsLayout::Unwrapped && inputs.len() != 1 {
+            return Err(BridgeError::InvalidSignature(format!(
+                "{signature} must take exactly one argument to be unwrapped"
+            )));
+        }
+        Ok(Self {
+            signature: signature.into(),
+            selector: selector(signature),
+            method,
+            inputs,
+            layout,
+        })
+    }
+}
+
+/// Routes Solidity selectors to the FRC-0042 methods of a native actor
+///
+/// ```
+/// use frc42_dispatch::method_hash;
+/// use frc42_dispatch::solidity::{ParamsLayout, SolidityBridge};
+///
+/// let bridge = SolidityBridge::default()
+///     .route("balanceOf(address)", method_hash!("BalanceOf"), ParamsLayout::Unwrapped)?
+///     .route("transfer(address,uint256,bytes)", method_hash!("Transfer"), ParamsLayout::Tuple)?;
+/// # Ok::<(), frc42_dispatch::solidity::BridgeError>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SolidityBridge {
+    methods: Vec<SolidityMethod>,
+}
+
+impl SolidityBridge {
+    /// Routes calls to a Solidity function signature to a method
+    pub fn route(
+        mut self,
+        signature: &str,
+        method: MethodNum,
+        layout: ParamsLayout,
+    ) -> Result<Self, BridgeError> {
+        let method = SolidityMethod::new(signature, method, layout)?;
+        if let Some(existing) = self.method(method.selector) {
+            return Err(BridgeError::DuplicateSelector(
+                method.selector,
+                existing.signature.clone(),
+            ));
+        }
+        self.methods.push(method);
+        Ok(self)
+    }
+
+    /// Looks up the method routed for a selector
+    pub fn method(&self, selector: Selector) -> Option<&SolidityMethod> {
+        self.methods.iter().find(|m| m.selector == selector)
+    }
+
+    /// Translates Solidity calldata into the method number and CBOR params of the native method
+    pub fn translate(
+        &self,
+        calldata: &[u8],
+    ) -> Result<(MethodNum, Option<IpldBlock>), BridgeError> {
+        if calldata.len() < 4 {
+            return Err(BridgeError::Calldata("calldata is shorter than a selector".into()));
+        }
+        let selector = [calldata[0], calldata[1], calldata[2], calldata[3]];
+        let method = self.method(selector).ok_or(BridgeError::UnknownSelector(selector))?;
+        let args = decode_args(&method.inputs, &calldata[4..])?;
+        let params = match (method.layout, args.first()) {
+            (_, None) => None,
+            (ParamsLayout::Unwrapped, Some(arg)) => IpldBlock::serialize_cbor(arg)?,
+            (ParamsLayout::Tuple, Some(_)) => IpldBlock::serialize_cbor(&args)?,
+        };
+        Ok((method.method, params))
+    }
+}
+
+/// Parses the argument types of a canonical Solidity signature such as `approve(address,uint256)`
+pub fn parse_signature(signature: &str) -> Result<Vec<AbiType>, BridgeError> {
+    let invalid = || BridgeError::InvalidSignature(signature.into());
+    let (name, args) = signature.split_once('(').ok_or_else(invalid)?;
+    let args = args.strip_suffix(')').ok_or_else(invalid)?;
+    let valid_name = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid_name {
+        return Err(invalid());
+    }
+    if args.is_empty() {
+        return Ok(Vec::new());
+    }
+    args.split(',').map(AbiType::from_str).collect()
+}
+
+/// Decodes ABI-encoded arguments of the given types
+///
+/// A canonical encoding is read one word at a time, without reading any word twice. Offsets
+/// pointing several dynamic values at the same data are rejected once more words have been read
+/// than the calldata holds, so a small payload can't decode into a large number of values.
+pub fn decode_args(types: &[AbiType], data: &[u8]) -> Result<Vec<AbiValue>, BridgeError> {
+    let mut budget = data.len().div_ceil(WORD);
+    decode_tuple(types, data, &mut budget)
+}
+
+fn decode_tuple(
+    types: &[AbiType],
+    data: &[u8],
+    budget: &mut usize,
+) -> Result<Vec<AbiValue>, BridgeError> {
+    types
+        .iter()
+        .enumerate()
+        .map(|(i, ty)| {
+            let head = word(data, i * WORD)?;
+            spend(budget, 1)?;
+            if ty.is_dynamic() {
+                decode_dynamic(ty, data.get(to_usize(head)?..).unwrap_or_default(), budget)
+            } else {
+                decode_static(ty, head)
+            }
+        })
+        .collect()
+}
+
+/// Accounts for reading `words` words of calldata
+fn spend(budget: &mut usize, words: usize) -> Result<(), BridgeError> {
+    *budget = budget
+        .checked_sub(words)
+        .ok_or_else(|| BridgeError::Calldata("offsets of dynamic values overlap".into()))?;
+    Ok(())
+}
+
+/// Encodes a call to a Solidity function, as a FEVM contract would
+pub fn encode_call(signature: &str, args: &[AbiValue]) -> Result<Vec<u8>, BridgeError> {
+    let types = parse_signature(signature)?;
+    if types.len() != args.len() {
+        return Err(BridgeError::InvalidArgument(format!(
+            "{signature} takes {} arguments but {} were given",
+            types.len(),
+            args.len()
+        )));
+    }
+    let mut calldata = selector(signature).to_vec();
+    calldata.extend(encode_args(&types, args)?);
+    Ok(calldata)
+}
+
+fn word(data: &[u8], offset: usize) -> Result<&[u8], BridgeError> {
+    data.get(offset..offset + WORD)
+        .ok_or_else(|| BridgeError::Calldata(format!("expected a word at offset {offset}")))
+}
+
+/// Reads a word holding an offset or length
+fn to_usize(word: &[u8]) -> Result<usize, BridgeError> {
+    if word[..WORD - 4].iter().any(|b| *b != 0) {
+        return Err(BridgeError::Calldata("offset or length out of range".into()));
+    }
+    Ok(u32::from_be_bytes([word[28], word[29], word[30], word[31]]) as usize)
+}
+
+fn decode_static(ty: &AbiType, word: &[u8]) -> Result<AbiValue, BridgeError> {
+    let leading_zeros = word.iter().take_while(|b| **b == 0).count();
+    match ty {
+        AbiType::Address => {
+            if leading_zeros < 12 {
+                return Err(BridgeError::Calldata("address has non-zero padding".into()));
+            }
+            Ok(AbiValue::Address(from_evm_address(&word[12..])?))
+        }
+        AbiType::Bool => match (leading_zeros >= WORD - 1, word[WORD - 1]) {
+            (true, 0) => Ok(AbiValue::Bool(false)),
+            (true, 1) => Ok(AbiValue::Bool(true)),
+            _ => Err(BridgeError::Calldata("bool is neither 0 nor 1".into())),
+        },
+        AbiType::Uint(bits) => {
+            if leading_zeros < WORD - *bits as usize / 8 {
+                return Err(BridgeError::Calldata(format!("value doesn't fit in a uint{bits}")));
+            }
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&word[WORD - 8..]);
+            Ok(AbiValue::Uint(u64::from_be_bytes(bytes)))
+        }
+        AbiType::Uint256 => {
+            Ok(AbiValue::Uint256(TokenAmount::from_atto(BigInt::from_bytes_be(Sign::Plus, word))))
+        }
+        _ => unreachable!("dynamic types are decoded from their offset"),
+    }
+}
+
+fn decode_dynamic(ty: &AbiType, data: &[u8], budget: &mut usize) -> Result<AbiValue, BridgeError> {
+    let length = to_usize(word(data, 0)?)?;
+    spend(budget, 1)?;
+    let body = &data[WORD..];
+    match ty {
+        AbiType::Bytes | AbiType::String => {
+            let bytes = body
+                .get(..length)
+                .ok_or_else(|| BridgeError::Calldata("bytes run past the end".into()))?
+                .to_vec();
+            spend(budget, length.div_ceil(WORD))?;
+            if *ty == AbiType::Bytes {
+                Ok(AbiValue::Bytes(bytes))
+            } else {
+                String::from_utf8(bytes)
+                    .map(AbiValue::String)
+                    .map_err(|_| BridgeError::Calldata("string is not valid UTF-8".into()))
+            }
+        }
+        AbiType::Array(inner) => {
+            // every element takes at least one word, so this bounds the allocation below
+            if length > body.len() / WORD {
+                return Err(BridgeError::Calldata("array runs past the end".into()));
+            }
+            decode_tuple(&vec![(**inner).clone(); length], body, budget).map(AbiValue::Array)
+        }
+        _ => unreachable!("static types are decoded in place"),
+    }
+}
+
+fn encode_args(types: &[AbiType], args: &[AbiValue]) -> Result<Vec<u8>, BridgeError> {
+    let mut heads = Vec::with_capacity(args.len() * WORD);
+    let mut tails = Vec::new();
+    for (ty, arg) in types.iter().zip(args.iter()) {
+        if ty.is_dynamic() {
+            heads.extend(uint_word((args.len() * WORD + tails.len()) as u64));
+            tails.extend(encode_dynamic(ty, arg)?);
+        } else {
+            heads.extend(encode_static(ty, arg)?);
+        }
+    }
+    heads.extend(tails);
+    Ok(heads)
+}
+
+fn uint_word(value: u64) -> [u8; WORD] {
+    let mut word = [0u8; WORD];
+    word[WORD - 8..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+fn mismatch(ty: &AbiType, arg: &AbiValue) -> BridgeError {
+    BridgeError::InvalidArgument(format!("{arg:?} is not a {ty}"))
+}
+
+fn encode_static(ty: &AbiType, arg: &AbiValue) -> Result<[u8; WORD], BridgeError> {
+    let mut word = [0u8; WORD];
+    match (ty, arg) {
+        (AbiType::Address, AbiValue::Address(address)) => {
+            word[12..].copy_from_slice(&to_evm_address(address)?)
+        }
+        (AbiType::Bool, AbiValue::Bool(value)) => word[WORD - 1] = *value as u8,
+        (AbiType::Uint(bits), AbiValue::Uint(value)) => {
+            if *bits < 64 && *value >> bits != 0 {
+                return Err(BridgeError::InvalidArgument(format!("{value} overflows uint{bits}")));
+            }
+            word = uint_word(*value)
+        }
+        (AbiType::Uint256, AbiValue::Uint256(amount)) => {
+            let (sign, bytes) = amount.atto().to_bytes_be();
+            if sign == Sign::Minus || bytes.len() > WORD {
+                return Err(BridgeError::InvalidArgument(format!("{amount} is not a uint256")));
+            }
+            word[WORD - bytes.len()..].copy_from_slice(&bytes);
+        }
+        _ => return Err(mismatch(ty, arg)),
+    }
+    Ok(word)
+}
+
+fn encode_dynamic(ty: &AbiType, arg: &AbiValue) -> Result<Vec<u8>, BridgeError> {
+    let bytes = match (ty, arg) {
+        (AbiType::Bytes, AbiValue::Bytes(bytes)) => bytes.as_slice(),
+        (AbiType::String, AbiValue::String(string)) => string.as_bytes(),
+        (AbiType::Array(inner), AbiValue::Array(values)) => {
+            let mut out = uint_word(values.len() as u64).to_vec();
+            out.extend(encode_args(&vec![(**inner).clone(); values.len()], values)?);
+            return Ok(out);
+        }
+        _ => return Err(mismatch(ty, arg)),
+    };
+    let mut out = uint_word(bytes.len() as u64).to_vec();
+    out.extend(bytes);
+    out.resize(WORD + bytes.len().div_ceil(WORD) * WORD, 0);
+    Ok(out)
+}
+
+/// Maps a 20 byte EVM address to a Filecoin address
+pub fn from_evm_address(bytes: &[u8]) -> Result<Address, BridgeError> {
+    // masked ID addresses are 0xff followed by zeros and a big-endian actor ID
+    if bytes[0] == 0xff && bytes[1..12].iter().all(|b| *b == 0) {
+        let mut id = [0u8; 8];
+        id.copy_from_slice(&bytes[12..]);
+        return Ok(Address::new_id(u64::from_be_bytes(id)));
+    }
+    Address::new_delegated(EAM_NAMESPACE, bytes)
+        .map_err(|e| BridgeError::Calldata(format!("invalid EVM address: {e}")))
+}
+
+/// Maps a Filecoin address to a 20 byte EVM address, if it has one
+pub fn to_evm_address(address: &Address) -> Result<[u8; 20], BridgeError> {
+    let mut bytes = [0u8; 20];
+    match address.payload() {
+        Payload::ID(id) => {
+            bytes[0] = 0xff;
+            bytes[12..].copy_from_slice(&id.to_be_bytes());
+        }
+        Payload::Delegated(delegated)
+            if delegated.namespace() == EAM_NAMESPACE && delegated.subaddress().len() == 20 =>
+        {
+            bytes.copy_from_slice(delegated.subaddress())
+        }
+        _ => {
+            return Err(BridgeError::InvalidArgument(format!(
+                "{address} can't be represented as an EVM address"
+            )))
+        }
+    }
+    Ok(bytes)
+}
+
+/// Computes the selector of a canonical Solidity function signature
+pub const fn selector(signature: &str) -> Selector {
+    let hash = keccak256(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+const KECCAK_RATE: usize = 136;
+
+const KECCAK_ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+const KECCAK_ROTATIONS: [u32; 24] =
+    [1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44];
+
+const KECCAK_LANES: [usize; 24] =
+    [10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1];
+
+/// Keccak-256 as used by Ethereum (the original padding, not SHA3-256)
+const fn keccak256(bytes: &[u8]) -> [u8; 32] {
+    let mut state = [0u64; 25];
+    // the padding always adds at least one byte, so there is always a final block
+    let blocks = bytes.len() / KECCAK_RATE + 1;
+    let mut block = 0;
+    while block < blocks {
+        let mut i = 0;
+        while i < KECCAK_RATE {
+            let offset = block * KECCAK_RATE + i;
+            let mut byte = if offset < bytes.len() { bytes[offset] } else { 0 };
+            if offset == bytes.len() {
+                byte ^= 0x01;
+            }
+            if block == blocks - 1 && i == KECCAK_RATE - 1 {
+                byte ^= 0x80;
+            }
+            state[i / 8] ^= (byte as u64) << (8 * (i % 8));
+            i += 1;
+        }
+        keccak_f(&mut state);
+        block += 1;
+    }
+
+    let mut out = [0u8; 32];
+    let mut i = 0;
+    while i < 32 {
+        out[i] = (state[i / 8] >> (8 * (i % 8))) as u8;
+        i += 1;
+    }
+    out
+}
+
+const fn keccak_f(state: &mut [u64; 25]) {
+    let mut round = 0;
+    while round < 24 {
+        // theta
+        let mut columns = [0u64; 5];
+        let mut x = 0;
+        while x < 5 {
+            columns[x] = state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
+            x += 1;
+        }
+        let mut x = 0;
+        while x < 5 {
+            let d = columns[(x + 4) % 5] ^ columns[(x + 1) % 5].rotate_left(1);
+            let mut y = 0;
+            while y < 25 {
+                state[y + x] ^= d;
+                y += 5;
+            }
+            x += 1;
+        }
+
+        // rho and pi
+        let mut last = state[1];
+        let mut i = 0;
+        while i < 24 {
+            let lane = KECCAK_LANES[i];
+            let next = state[lane];
+            state[lane] = last.rotate_left(KECCAK_ROTATIONS[i]);
+            last = next;
+            i += 1;
+        }
+
+        // chi
+        let mut y = 0;
+        while y < 25 {
+            let row = [state[y], state[y + 1], state[y + 2], state[y + 3], state[y + 4]];
+            let mut x = 0;
+            while x < 5 {
+                state[y + x] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+                x += 1;
+            }
+            y += 5;
+        }
+
+        // iota
+        state[0] ^= KECCAK_ROUND_CONSTANTS[round];
+        round += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fvm_ipld_encoding::RawBytes;
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+
+    use super::{
+        decode_args, encode_call, from_evm_address, keccak256, parse_signature, selector, AbiType,
+        AbiValue, BridgeError, ParamsLayout, SolidityBridge, INVOKE_EVM_METHOD_NUM,
+    };
+    use crate::method_hash;
+
+    const TRANSFER: &str = "transfer(address,uint256,bytes)";
+
+    fn bridge() -> SolidityBridge {
+        SolidityBridge::default()
+            .route("balanceOf(address)", method_hash!("BalanceOf"), ParamsLayout::Unwrapped)
+            .unwrap()
+            .route(TRANSFER, method_hash!("Transfer"), ParamsLayout::Tuple)
+            .unwrap()
+            .route("totalSupply()", method_hash!("TotalSupply"), ParamsLayout::Tuple)
+            .unwrap()
+    }
+
+    #[test]
+    fn computes_selectors() {
+        assert_eq!(keccak256(b"")[..4], [0xc5, 0xd2, 0x46, 0x01], "keccak256 of the empty string");
+        assert_eq!(selector("transfer(address,uint256)"), [0xa9, 0x05, 0x9c, 0xbb]);
+        assert_eq!(selector("balanceOf(address)"), [0x70, 0xa0, 0x82, 0x31]);
+        assert_eq!(selector("totalSupply()"), [0x18, 0x16, 0x0d, 0xdd]);
+        assert_eq!(INVOKE_EVM_METHOD_NUM, method_hash!("InvokeEVM"));
+    }
+
+    #[test]
+    fn parses_signatures() {
+        assert_eq!(
+            parse_signature("mint(address,uint64[],string)").unwrap(),
+            vec![AbiType::Address, AbiType::Array(Box::new(AbiType::Uint(64))), AbiType::String]
+        );
+        assert!(parse_signature("totalSupply()").unwrap().is_empty());
+        assert!(matches!(
+            parse_signature("transfer(address, uint256)"),
+            Err(BridgeError::UnsupportedType(_))
+        ));
+        assert!(matches!(parse_signature("transfer"), Err(BridgeError::InvalidSignature(_))));
+        assert!(matches!(parse_signature("f(int256)"), Err(BridgeError::UnsupportedType(_))));
+    }
+
+    #[test]
+    fn translates_calls() {
+        let bridge = bridge();
+        let to = from_evm_address(&[0x11; 20]).unwrap();
+        let amount = TokenAmount::from_atto(1_000_000_000_000_000_000u64);
+        let calldata = encode_call(
+            TRANSFER,
+            &[
+                AbiValue::Address(to),
+                AbiValue::Uint256(amount.clone()),
+                AbiValue::Bytes(vec![1, 2, 3]),
+            ],
+        )
+        .unwrap();
+        // selector, three heads, then the length and one padded word of bytes
+        assert_eq!(calldata.len(), 4 + 5 * 32);
+
+        let (method, params) = bridge.translate(&calldata).unwrap();
+        assert_eq!(method, method_hash!("Transfer"));
+        // the params of a tuple-serialized struct like TransferParams in frc46_token
+        let params: (Address, TokenAmount, RawBytes) = params.unwrap().deserialize().unwrap();
+        assert_eq!(params, (to, amount, RawBytes::new(vec![1, 2, 3])));
+
+        let calldata =
+            encode_call("balanceOf(address)", &[AbiValue::Address(Address::new_id(1234))]).unwrap();
+        let (method, params) = bridge.translate(&calldata).unwrap();
+        assert_eq!(method, method_hash!("BalanceOf"));
+        assert_eq!(params.unwrap().deserialize::<Address>().unwrap(), Address::new_id(1234));
+
+        let (method, params) = bridge.translate(&selector("totalSupply()")).unwrap();
+        assert_eq!(method, method_hash!("TotalSupply"));
+        assert!(params.is_none());
+    }
+
+    #[test]
+    fn roundtrips_dynamic_arrays() {
+        let types = parse_signature("f(string[],uint64[],bool)").unwrap();
+        let args = vec![
+            AbiValue::Array(vec![AbiValue::String("one".into()), AbiValue::String("two".into())]),
+            AbiValue::Array(vec![AbiValue::Uint(1), AbiValue::Uint(u64::MAX)]),
+            AbiValue::Bool(true),
+        ];
+        let calldata = encode_call("f(string[],uint64[],bool)", &args).unwrap();
+        assert_eq!(decode_args(&types, &calldata[4..]).unwrap(), args);
+
+        // nested arrays read every word of the calldata exactly once
+        let types = parse_signature("f(string[][])").unwrap();
+        let args = vec![AbiValue::Array(vec![
+            AbiValue::Array(vec![AbiValue::String("one".into()), AbiValue::String("".into())]),
+            AbiValue::Array(vec![]),
+        ])];
+        let calldata = encode_call("f(string[][])", &args).unwrap();
+        assert_eq!(decode_args(&types, &calldata[4..]).unwrap(), args);
+    }
+
+    #[test]
+    fn rejects_bad_calls() {
+        let bridge = bridge();
+        assert!(matches!(bridge.translate(&[0xa9, 0x05]), Err(BridgeError::Calldata(_))));
+        assert!(matches!(
+            bridge.translate(&selector("approve(address,uint256)")),
+            Err(BridgeError::UnknownSelector(_))
+        ));
+        // truncated arguments
+        let calldata =
+            encode_call("balanceOf(address)", &[AbiValue::Address(Address::new_id(1))]).unwrap();
+        assert!(matches!(bridge.translate(&calldata[..20]), Err(BridgeError::Calldata(_))));
+        // a uint8 that overflows
+        let mut calldata = selector("f(uint8)").to_vec();
+        calldata.extend([0u8; 30]);
+        calldata.extend([1, 0]);
+        assert!(decode_args(&[AbiType::Uint(8)], &calldata[4..]).is_err());
+
+        // every element of a uint64[][] pointing at the same inner array
+        let mut calldata = super::uint_word(32).to_vec();
+        calldata.extend(super::uint_word(64));
+        calldata.extend([super::uint_word(64 * 32); 64].concat());
+        calldata.extend(super::uint_word(64));
+        calldata.extend([super::uint_word(1); 64].concat());
+        let types = parse_signature("f(uint64[][])").unwrap();
+        assert!(matches!(
+            decode_args(&types, &calldata),
+            Err(BridgeError::Calldata(message)) if message.contains("overlap")
+        ));
+
+        assert!(matches!(
+            bridge.clone().route(TRANSFER, 1, ParamsLayout::Tuple),
+            Err(BridgeError::DuplicateSelector(..))
+        ));
+        assert!(matches!(
+            bridge.route("approve(address,uint256)", 1, ParamsLayout::Unwrapped),
+            Err(BridgeError::InvalidSignature(_))
+        ));
+        assert!(matches!(
+            encode_call("balanceOf(address)", &[AbiValue::Bool(true)]),
+            Err(BridgeError::InvalidArgument(_))
+        ));
+    }
+}
@@ -0,0 +1,103 @@
+//! Rules for reading the FRC-0042 macro arguments and method signatures
+//!
+//! Shared by `frc42_macros` and `fvm_dispatch_tools`, so manifests and scans generated from sources
+//! always agree with the macros.
+
+use frc42_hasher::convention::Convention;
+use syn::parse::{Parse, ParseStream};
+use syn::{GenericArgument, Ident, LitInt, LitStr, PathArguments, ReturnType, Token, Type};
+
+/// Parses comma separated `version = N` or `prefix = "..."` arguments selecting the hashing
+/// convention, returning `None` if there are none
+///
+/// A prefix keeps the reserved range of version 1. At most one argument may be given.
+pub fn parse_convention(input: ParseStream) -> syn::Result<Option<Convention>> {
+    let mut convention = None;
+    while !input.is_empty() {
+        let key: Ident = input.parse()?;
+        input.parse::<Token![=]>()?;
+        if convention.is_some() {
+            return Err(syn::Error::new(key.span(), "the hashing convention is already set"));
+        }
+        convention = Some(match key.to_string().as_str() {
+            "version" => {
+                let version: LitInt = input.parse()?;
+                Convention::version(version.base10_parse()?).ok_or_else(|| {
+                    syn::Error::new(version.span(), "unknown FRC-0042 convention version")
+                })?
+            }
+            "prefix" => Convention::V1.with_prefix(input.parse::<LitStr>()?.value()),
+            _ => return Err(syn::Error::new(key.span(), "expected `version` or `prefix`")),
+        });
+        if !input.is_empty() {
+            input.parse::<Token![,]>()?;
+        }
+    }
+    Ok(convention)
+}
+
+/// Arguments of a `#[method("Name")]` or `#[method("Name", result)]` attribute
+pub struct MethodAttr {
+    pub name: LitStr,
+    /// Whether the return type was explicitly marked as a `Result`
+    pub result: bool,
+}
+
+impl Parse for MethodAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        let mut result = false;
+        if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            let flag: Ident = input.parse()?;
+            if flag != "result" {
+                return Err(syn::Error::new(flag.span(), "expected `result`"));
+            }
+            result = true;
+            input.parse::<Option<Token![,]>>()?;
+        }
+        Ok(Self { name, result })
+    }
+}
+
+/// What an exported method returns
+pub enum Returns<'a> {
+    /// Nothing, or `()`
+    Unit,
+    /// A serializable value
+    Value(&'a Type),
+    /// A `Result`, with its `Ok` type if that is spelled out
+    Result(Option<&'a Type>),
+}
+
+/// Classifies a method's return type
+///
+/// Only types named exactly `Result` are treated as fallible, unless `result` is set to mark an
+/// alias such as `ActorResult<T>`.
+pub fn returns(output: &ReturnType, result: bool) -> Returns<'_> {
+    let ty = match output {
+        ReturnType::Type(_, ty) if !is_unit(ty) => &**ty,
+        _ => return Returns::Unit,
+    };
+    let segment = match ty {
+        Type::Path(path) => path.path.segments.last(),
+        _ => None,
+    };
+    match segment {
+        Some(segment) if result || segment.ident == "Result" => {
+            let ok = match &segment.arguments {
+                PathArguments::AngleBracketed(args) => args.args.iter().find_map(|arg| match arg {
+                    GenericArgument::Type(ty) => Some(ty),
+                    _ => None,
+                }),
+                _ => None,
+            };
+            Returns::Result(ok)
+        }
+        _ if result => Returns::Result(None),
+        _ => Returns::Value(ty),
+    }
+}
+
+pub fn is_unit(ty: &Type) -> bool {
+    matches!(ty, Type::Tuple(tuple) if tuple.elems.is_empty())
+}
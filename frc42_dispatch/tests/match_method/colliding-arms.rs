@@ -0,0 +1,12 @@
+use frc42_dispatch::match_method;
+
+fn main() {
+	// should fail because both names hash to the same method number
+	let method_num = 1u64;
+	let _ret = match_method!(method_num, {
+		"Constructor" => Some(1),
+		"Method46776" => Some(2),
+		"Method85157" => Some(3),
+		_ => None,
+	});
+}
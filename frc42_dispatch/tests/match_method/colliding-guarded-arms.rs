@@ -0,0 +1,12 @@
+use frc42_dispatch::match_method;
+
+fn main() {
+	// should fail because both names hash to the same method number, even though one arm is guarded
+	let method_num = 1u64;
+	let migrated = true;
+	let _ret = match_method!(method_num, {
+		"Method46776" if migrated => Some(1),
+		"Method85157" => Some(2),
+		_ => None,
+	});
+}
@@ -0,0 +1,11 @@
+use frc42_dispatch::match_method;
+
+fn main() {
+	// should fail because "Transfer" is matched by two unguarded arms
+	let method_num = 1u64;
+	let _ret = match_method!(method_num, {
+		"Transfer" | "TransferFrom" => Some(1),
+		"Transfer" => Some(2),
+		_ => None,
+	});
+}
@@ -0,0 +1,13 @@
+use frc42_dispatch::match_method;
+
+fn main() {
+	// should fail because "Constructor" is matched twice
+	let method_num = 1u64;
+	let _ret = match_method!(method_num, {
+		"Constructor" => Some(1),
+		"Constructor" => {
+			Some(2)
+		}
+		_ => None,
+	});
+}
@@ -0,0 +1,11 @@
+use frc42_dispatch::match_method;
+
+fn main() {
+	// should fail because "Exports" is handled by both a named arm and the exports arm
+	let method_num = 1u64;
+	let _ret = match_method!(method_num, {
+		"Exports" => None,
+		exports(methods) => Some(methods),
+		_ => None,
+	});
+}
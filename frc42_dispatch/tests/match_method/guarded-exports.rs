@@ -0,0 +1,12 @@
+use frc42_dispatch::match_method;
+
+fn main() {
+	// should fail because the exports arm is matched first, so the guarded arm is unreachable
+	let method_num = 1u64;
+	let migrated = true;
+	let _ret = match_method!(method_num, {
+		"Exports" if migrated => None,
+		exports(methods) => Some(methods),
+		_ => None,
+	});
+}
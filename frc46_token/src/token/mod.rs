@@ -2,6 +2,7 @@ use std::ops::Neg;
 
 use cid::Cid;
 pub use error::TokenError;
+use frc42_dispatch::interface::{interface_id, InterfaceId};
 use fvm_actor_utils::messaging::{MessagingError, RECEIVER_HOOK_METHOD_NUM};
 use fvm_actor_utils::receiver::{ReceiverHook, ReceiverHookError};
 use fvm_actor_utils::syscalls::Syscalls;
@@ -32,6 +33,26 @@ pub mod types;
 /// Aka "18 decimals".
 pub const TOKEN_PRECISION: u64 = 1_000_000_000_000_000_000;
 
+/// Methods a token actor must export to implement FRC-0046
+pub const FRC46_METHOD_NAMES: [&str; 13] = [
+    "Name",
+    "Symbol",
+    "Granularity",
+    "TotalSupply",
+    "BalanceOf",
+    "Allowance",
+    "IncreaseAllowance",
+    "DecreaseAllowance",
+    "RevokeAllowance",
+    "Burn",
+    "BurnFrom",
+    "Transfer",
+    "TransferFrom",
+];
+
+/// Interface ID of FRC-0046, for answering `SupportsInterface` queries
+pub const FRC46_INTERFACE_ID: InterfaceId = interface_id(&FRC46_METHOD_NAMES);
+
 type Result<T> = std::result::Result<T, TokenError>;
 
 /// Library functions that implement core FRC-??? standards
@@ -702,22 +723,34 @@ pub fn validate_allowance<'a>(a: &'a TokenAmount, name: &'static str) -> Result<
 mod test {
     use std::ops::Neg;
 
+    use frc42_dispatch::interface::{interface_id, supports_interface, InterfaceId};
+    use frc42_dispatch::method_hash;
+    use fvm_actor_utils::gas::{GasSnapshot, PriceList};
     use fvm_actor_utils::messaging::{MessagingError, RECEIVER_HOOK_METHOD_NUM};
-    use fvm_actor_utils::receiver::{ReceiverHookError, UniversalReceiverParams};
+    use fvm_actor_utils::receiver::{
+        ReceiverHookError, UniversalReceiverParams, UNIVERSAL_RECEIVER_INTERFACE_ID,
+    };
     use fvm_actor_utils::syscalls::fake_syscalls::FakeSyscalls;
+    use fvm_actor_utils::syscalls::mock_world::{ActorAbort, InvokeResult, MockRuntime, MockWorld};
+    use fvm_actor_utils::tracking_blockstore::{BlockstoreStats, TrackingBlockstore};
     use fvm_actor_utils::util::ActorRuntime;
     use fvm_ipld_blockstore::MemoryBlockstore;
+    use fvm_ipld_encoding::ipld_block::IpldBlock;
     use fvm_ipld_encoding::RawBytes;
     use fvm_sdk::sys::ErrorNumber;
     use fvm_shared::address::{Address, BLS_PUB_LEN};
     use fvm_shared::econ::TokenAmount;
+    use fvm_shared::error::ExitCode;
+    use fvm_shared::{MethodNum, Response, METHOD_SEND};
     use num_traits::Zero;
 
     use crate::receiver::{FRC46TokenReceived, FRC46_TOKEN_TYPE};
     use crate::token::state::StateError;
     use crate::token::state::TokenState;
+    use crate::token::types::MintReturn;
     use crate::token::Token;
     use crate::token::TokenError;
+    use crate::token::{FRC46_INTERFACE_ID, FRC46_METHOD_NAMES};
 
     /// Returns a static secp256k1 address
     fn secp_address() -> Address {
@@ -762,6 +795,17 @@ mod test {
         assert_eq!(last_called, expected);
     }
 
+    #[test]
+    fn it_exports_the_frc46_interface_id() {
+        // pinned so that changes to the method list or the hashing are caught
+        assert_eq!(FRC46_INTERFACE_ID, 0x48e5ae07);
+        // the last two methods are the transfers
+        let transfers = (method_hash!("Transfer") ^ method_hash!("TransferFrom")) as InterfaceId;
+        assert_eq!(interface_id(&FRC46_METHOD_NAMES[..11]) ^ transfers, FRC46_INTERFACE_ID);
+        assert!(supports_interface(&[FRC46_INTERFACE_ID], FRC46_INTERFACE_ID));
+        assert!(!supports_interface(&[FRC46_INTERFACE_ID], UNIVERSAL_RECEIVER_INTERFACE_ID));
+    }
+
     #[test]
     fn it_wraps_a_previously_loaded_state_tree() {
         struct ActorState {
@@ -1154,6 +1198,47 @@ mod test {
         token.assert_invariants().unwrap();
     }
 
+    #[test]
+    fn it_initialises_accounts_before_calling_the_receiver_hook() {
+        let mut helper = ActorRuntime::<FakeSyscalls, MemoryBlockstore>::new_test_runtime();
+        helper.syscalls.actor_id = TOKEN_ACTOR.id().unwrap();
+
+        let mut token_state =
+            Token::<FakeSyscalls, MemoryBlockstore>::create_state(helper.bs()).unwrap();
+        let mut token = new_token(&helper, &mut token_state);
+
+        let secp_address = secp_address();
+        let mut hook = token
+            .mint(
+                TOKEN_ACTOR,
+                &secp_address,
+                &TokenAmount::from_atto(1_000_000),
+                RawBytes::default(),
+                RawBytes::default(),
+            )
+            .unwrap();
+        token.flush().unwrap();
+        hook.call(token.runtime).unwrap();
+
+        // the account was initialised with a bare send before the receiver hook was called on it
+        let methods: Vec<_> =
+            token.runtime.syscalls.sent_messages().iter().map(|m| m.method).collect();
+        assert_eq!(methods, [METHOD_SEND, RECEIVER_HOOK_METHOD_NUM]);
+        let received: FRC46TokenReceived =
+            token.runtime.syscalls.expect_receiver_hook(&secp_address, FRC46_TOKEN_TYPE);
+        assert_eq!(
+            received,
+            FRC46TokenReceived {
+                operator: TOKEN_ACTOR.id().unwrap(),
+                from: TOKEN_ACTOR.id().unwrap(),
+                to: token.runtime.resolve_id(&secp_address).unwrap(),
+                amount: TokenAmount::from_atto(1_000_000),
+                operator_data: Default::default(),
+                token_data: Default::default(),
+            }
+        );
+    }
+
     #[test]
     fn it_fails_to_mint_if_receiver_hook_aborts() {
         let helper = ActorRuntime::<FakeSyscalls, MemoryBlockstore>::new_test_runtime();
@@ -1410,6 +1495,127 @@ mod test {
         );
     }
 
+    #[test]
+    fn it_measures_ipld_cost_of_transfers() {
+        let helper = ActorRuntime::<FakeSyscalls, MemoryBlockstore>::new_tracking_test_runtime();
+        let mut token_state =
+            Token::<FakeSyscalls, TrackingBlockstore<MemoryBlockstore>>::create_state(helper.bs())
+                .unwrap();
+        let mut token = Token::wrap(&helper, 1, &mut token_state);
+
+        for owner in [ALICE, BOB] {
+            let mut hook = token
+                .mint(
+                    TOKEN_ACTOR,
+                    owner,
+                    &TokenAmount::from_atto(100),
+                    RawBytes::default(),
+                    RawBytes::default(),
+                )
+                .unwrap();
+            token.flush().unwrap();
+            hook.call(token.runtime).unwrap();
+        }
+
+        fn transfer(
+            token: &mut Token<FakeSyscalls, TrackingBlockstore<MemoryBlockstore>>,
+        ) -> BlockstoreStats {
+            token.runtime.bs().reset();
+            let mut hook = token
+                .transfer(
+                    ALICE,
+                    BOB,
+                    &TokenAmount::from_atto(10),
+                    RawBytes::default(),
+                    RawBytes::default(),
+                )
+                .unwrap();
+            token.flush().unwrap();
+            hook.call(token.runtime).unwrap();
+            token.runtime.bs().take_stats()
+        }
+
+        // the balance map is loaded and flushed once for each holder, then the new state root is
+        // written
+        let first = transfer(&mut token);
+        assert_eq!(first.gets, 2);
+        assert_eq!(first.puts, 3);
+        assert!(first.bytes_read > 0 && first.bytes_written > 0);
+
+        // transfers between existing holders always cost the same
+        let second = transfer(&mut token);
+        assert_eq!(second.gets, first.gets);
+        assert_eq!(second.puts, first.puts);
+    }
+
+    #[test]
+    fn it_matches_the_gas_snapshot() {
+        let prices = PriceList::default();
+        let mut snapshot =
+            GasSnapshot::new(concat!(env!("CARGO_MANIFEST_DIR"), "/gas_snapshot.txt"));
+        let helper = ActorRuntime::<FakeSyscalls, MemoryBlockstore>::new_tracking_test_runtime();
+        let mut token_state =
+            Token::<FakeSyscalls, TrackingBlockstore<MemoryBlockstore>>::create_state(helper.bs())
+                .unwrap();
+        let mut token = Token::wrap(&helper, 1, &mut token_state);
+        token.flush().unwrap();
+        snapshot.record("create_state", prices.take_estimate(&helper));
+
+        for name in ["mint_to_new_holder", "mint_to_existing_holder"] {
+            let mut hook = token
+                .mint(
+                    TOKEN_ACTOR,
+                    ALICE,
+                    &TokenAmount::from_atto(100),
+                    RawBytes::default(),
+                    RawBytes::default(),
+                )
+                .unwrap();
+            token.flush().unwrap();
+            hook.call(token.runtime).unwrap();
+            snapshot.record(name, prices.take_estimate(&helper));
+        }
+
+        for name in ["transfer_to_new_holder", "transfer_to_existing_holder"] {
+            let mut hook = token
+                .transfer(
+                    ALICE,
+                    BOB,
+                    &TokenAmount::from_atto(10),
+                    RawBytes::default(),
+                    RawBytes::default(),
+                )
+                .unwrap();
+            token.flush().unwrap();
+            hook.call(token.runtime).unwrap();
+            snapshot.record(name, prices.take_estimate(&helper));
+        }
+
+        token.increase_allowance(ALICE, CAROL, &TokenAmount::from_atto(50)).unwrap();
+        token.flush().unwrap();
+        snapshot.record("increase_allowance", prices.take_estimate(&helper));
+
+        let mut hook = token
+            .transfer_from(
+                CAROL,
+                ALICE,
+                BOB,
+                &TokenAmount::from_atto(10),
+                RawBytes::default(),
+                RawBytes::default(),
+            )
+            .unwrap();
+        token.flush().unwrap();
+        hook.call(token.runtime).unwrap();
+        snapshot.record("transfer_from", prices.take_estimate(&helper));
+
+        token.burn(ALICE, &TokenAmount::from_atto(10)).unwrap();
+        token.flush().unwrap();
+        snapshot.record("burn", prices.take_estimate(&helper));
+
+        snapshot.assert_matches();
+    }
+
     #[test]
     fn it_transfers_to_self() {
         let helper = ActorRuntime::<FakeSyscalls, MemoryBlockstore>::new_test_runtime();
@@ -2832,5 +3038,119 @@ mod test {
         );
     }
 
-    // TODO: test for re-entrancy bugs by implementing a MethodCaller that calls back on the token contract
+    const MINT: MethodNum = method_hash!("Mint");
+    const TRANSFER: MethodNum = method_hash!("Transfer");
+
+    /// Hosts a token in a MockWorld the way an on-chain actor would, loading state from the root
+    /// for each message and flushing it before calling a receiver hook, which may re-enter it
+    fn token_actor(rt: &MockRuntime, method: MethodNum, params: Option<IpldBlock>) -> InvokeResult {
+        let abort = |e: TokenError| ActorAbort::from(ExitCode::from(&e));
+        let hook_abort = |e: ReceiverHookError| ActorAbort::from(ExitCode::from(&e));
+        let mut state = TokenState::load(rt.bs(), &rt.root_cid().unwrap()).unwrap();
+        let mut token = Token::wrap(rt, 1, &mut state);
+        let caller = Address::new_id(rt.caller());
+        let (to, amount): (Address, TokenAmount) = params.unwrap().deserialize().unwrap();
+
+        match method {
+            MINT => {
+                let mut hook = token
+                    .mint(&caller, &to, &amount, RawBytes::default(), RawBytes::default())
+                    .map_err(abort)?;
+                rt.set_root(&token.flush().map_err(abort)?).unwrap();
+                let intermediate = hook.call(rt).map_err(hook_abort)?;
+                // the hook may have changed the state
+                token.load_replace(&rt.root_cid().unwrap()).map_err(abort)?;
+                let ret = token.mint_return(intermediate).map_err(abort)?;
+                Ok(IpldBlock::serialize_cbor(&ret).unwrap())
+            }
+            TRANSFER => {
+                let mut hook = token
+                    .transfer(&caller, &to, &amount, RawBytes::default(), RawBytes::default())
+                    .map_err(abort)?;
+                rt.set_root(&token.flush().map_err(abort)?).unwrap();
+                hook.call(rt).map_err(hook_abort)?;
+                Ok(None)
+            }
+            _ => Err(ExitCode::USR_UNHANDLED_MESSAGE.into()),
+        }
+    }
+
+    /// Accepts tokens and immediately transfers 40 of them on to BOB, re-entering the token
+    fn forwarding_receiver(
+        rt: &MockRuntime,
+        method: MethodNum,
+        params: Option<IpldBlock>,
+    ) -> InvokeResult {
+        assert_eq!(method, RECEIVER_HOOK_METHOD_NUM);
+        let params: UniversalReceiverParams = params.unwrap().deserialize().unwrap();
+        assert_eq!(params.type_, FRC46_TOKEN_TYPE);
+
+        let transfer = IpldBlock::serialize_cbor(&(*BOB, TokenAmount::from_atto(40))).unwrap();
+        let token = Address::new_id(rt.caller());
+        let ret = rt.send(&token, TRANSFER, transfer, TokenAmount::zero()).unwrap();
+        match ret.exit_code {
+            ExitCode::OK => Ok(None),
+            code => Err(code.into()),
+        }
+    }
+
+    fn token_world() -> MockWorld {
+        let world = MockWorld::new();
+        world.install_actor(TOKEN_ACTOR.id().unwrap(), token_actor);
+        world.install_account(TREASURY.id().unwrap());
+        world.install_account(BOB.id().unwrap());
+
+        let rt = world.runtime(TOKEN_ACTOR.id().unwrap());
+        let state = TokenState::new(rt.bs()).unwrap();
+        rt.set_root(&state.save(rt.bs()).unwrap()).unwrap();
+        world
+    }
+
+    fn mint_in_world(world: &MockWorld, to: &Address) -> Response {
+        let params = IpldBlock::serialize_cbor(&(*to, TokenAmount::from_atto(100))).unwrap();
+        world
+            .apply_message(TREASURY.id().unwrap(), TOKEN_ACTOR, MINT, params, TokenAmount::zero())
+            .unwrap()
+    }
+
+    fn balance_in_world(world: &MockWorld, owner: &Address) -> TokenAmount {
+        let rt = world.runtime(TOKEN_ACTOR.id().unwrap());
+        let mut state = TokenState::load(rt.bs(), &rt.root_cid().unwrap()).unwrap();
+        let token = Token::wrap(&rt, 1, &mut state);
+        token.assert_invariants().unwrap();
+        token.balance_of(owner).unwrap()
+    }
+
+    #[test]
+    fn it_handles_reentrant_receiver_hooks() {
+        let world = token_world();
+        world.install_actor(ALICE.id().unwrap(), forwarding_receiver);
+
+        let ret = mint_in_world(&world, ALICE);
+        assert_eq!(ret.exit_code, ExitCode::OK);
+        // the mint reports the balance left after the hook re-entered the token
+        let ret: MintReturn = ret.return_data.unwrap().deserialize().unwrap();
+        assert_eq!(ret.balance, TokenAmount::from_atto(60));
+        assert_eq!(ret.supply, TokenAmount::from_atto(100));
+
+        assert_eq!(balance_in_world(&world, ALICE), TokenAmount::from_atto(60));
+        assert_eq!(balance_in_world(&world, BOB), TokenAmount::from_atto(40));
+    }
+
+    #[test]
+    fn it_rolls_back_reentrant_transfers_when_the_hook_aborts() {
+        let world = token_world();
+        // re-enters the token to transfer the tokens away, then rejects them anyway
+        world.install_actor(CAROL.id().unwrap(), |rt, method, params| {
+            forwarding_receiver(rt, method, params)?;
+            Err(ExitCode::USR_FORBIDDEN.into())
+        });
+        let root = world.root(TOKEN_ACTOR.id().unwrap());
+
+        let ret = mint_in_world(&world, CAROL);
+        assert_eq!(ret.exit_code, ExitCode::USR_FORBIDDEN);
+        assert_eq!(world.root(TOKEN_ACTOR.id().unwrap()), root);
+        assert_eq!(balance_in_world(&world, CAROL), TokenAmount::zero());
+        assert_eq!(balance_in_world(&world, BOB), TokenAmount::zero());
+    }
 }
@@ -7,6 +7,7 @@
 //! in many cases.
 
 use cid::Cid;
+use frc42_dispatch::interface::{interface_id, InterfaceId};
 use fvm_actor_utils::{
     messaging::MessagingError,
     receiver::ReceiverHook,
@@ -31,6 +32,32 @@ pub mod state;
 pub mod types;
 pub mod util;
 
+/// Methods an NFT actor must export to implement FRC-0053
+pub const FRC53_METHOD_NAMES: [&str; 19] = [
+    "Name",
+    "Symbol",
+    "Metadata",
+    "TotalSupply",
+    "Burn",
+    "ListTokens",
+    "BalanceOf",
+    "OwnerOf",
+    "Transfer",
+    "TransferFrom",
+    "Approve",
+    "Revoke",
+    "ApproveForAll",
+    "RevokeForAll",
+    "IsApprovedForAll",
+    "ListOwnedTokens",
+    "ListTokenOperators",
+    "ListOperatorTokens",
+    "ListAccountOperators",
+];
+
+/// Interface ID of FRC-0053, for answering `SupportsInterface` queries
+pub const FRC53_INTERFACE_ID: InterfaceId = interface_id(&FRC53_METHOD_NAMES);
+
 #[derive(Error, Debug)]
 pub enum NFTError {
     #[error("error in underlying state {0}")]
@@ -518,13 +545,20 @@ impl Cursor {
 #[cfg(test)]
 mod test {
 
-    use fvm_actor_utils::{syscalls::fake_syscalls::FakeSyscalls, util::ActorRuntime};
+    use frc42_dispatch::interface::supports_interface;
+    use fvm_actor_utils::{
+        gas::{GasSnapshot, PriceList},
+        receiver::UNIVERSAL_RECEIVER_INTERFACE_ID,
+        syscalls::fake_syscalls::FakeSyscalls,
+        tracking_blockstore::TrackingBlockstore,
+        util::ActorRuntime,
+    };
     use fvm_ipld_bitfield::bitfield;
     use fvm_ipld_blockstore::MemoryBlockstore;
     use fvm_ipld_encoding::RawBytes;
     use fvm_shared::{address::Address, ActorID};
 
-    use crate::{state::StateError, types::TokenID, NFTError, NFTState, NFT};
+    use crate::{state::StateError, types::TokenID, NFTError, NFTState, FRC53_INTERFACE_ID, NFT};
 
     const ALICE_ID: ActorID = 1;
     const ALICE: Address = Address::new_id(ALICE_ID);
@@ -533,6 +567,14 @@ mod test {
     const CHARLIE_ID: ActorID = 111;
     const CHARLIE: Address = Address::new_id(CHARLIE_ID);
 
+    #[test]
+    fn it_exports_the_frc53_interface_id() {
+        // pinned so that changes to the method list or the hashing are caught
+        assert_eq!(FRC53_INTERFACE_ID, 0xb1e91f33);
+        assert!(supports_interface(&[FRC53_INTERFACE_ID], FRC53_INTERFACE_ID));
+        assert!(!supports_interface(&[FRC53_INTERFACE_ID], UNIVERSAL_RECEIVER_INTERFACE_ID));
+    }
+
     #[test]
     fn it_mints_tokens_incrementally() {
         let helper = ActorRuntime::<FakeSyscalls, MemoryBlockstore>::new_test_runtime();
@@ -1326,4 +1368,63 @@ mod test {
             assert!(res.operators.is_empty());
         }
     }
+
+    #[test]
+    fn it_measures_ipld_cost_of_minting() {
+        let bs = TrackingBlockstore::new(MemoryBlockstore::new());
+        let mut state = NFTState::new(&bs).unwrap();
+
+        // each mint loads and flushes both the token array and the owner map
+        bs.reset();
+        state.mint_tokens(&bs, ALICE_ID, vec![String::new(); 1]).unwrap();
+        let single = bs.take_stats();
+        assert_eq!(single.gets, 2);
+        assert_eq!(single.puts, 2);
+        assert!(single.bytes_written > 0);
+
+        // minting a batch in one call amortises those flushes
+        for _ in 0..10 {
+            state.mint_tokens(&bs, BOB_ID, vec![String::new(); 1]).unwrap();
+        }
+        let one_by_one = bs.take_stats();
+        state.mint_tokens(&bs, CHARLIE_ID, vec![String::new(); 10]).unwrap();
+        let batched = bs.take_stats();
+        assert_eq!((one_by_one.gets, one_by_one.puts), (20, 20));
+        assert_eq!((batched.gets, batched.puts), (2, 2));
+    }
+
+    #[test]
+    fn it_matches_the_gas_snapshot() {
+        let prices = PriceList::default();
+        let mut snapshot =
+            GasSnapshot::new(concat!(env!("CARGO_MANIFEST_DIR"), "/gas_snapshot.txt"));
+        let helper = ActorRuntime::<FakeSyscalls, MemoryBlockstore>::new_tracking_test_runtime();
+        let mut state = NFTState::new(&helper).unwrap();
+        let mut nft = NFT::wrap(helper, &mut state);
+        snapshot.record("create_state", prices.take_estimate(&nft.runtime));
+
+        for (name, count) in [("mint_one", 1), ("mint_ten", 10)] {
+            let mut hook = nft
+                .mint(
+                    &ALICE,
+                    &ALICE,
+                    vec![String::new(); count],
+                    RawBytes::default(),
+                    RawBytes::default(),
+                )
+                .unwrap();
+            hook.call(&nft.runtime).unwrap();
+            snapshot.record(name, prices.take_estimate(&nft.runtime));
+        }
+
+        let mut hook =
+            nft.transfer(&ALICE, &BOB, &[0], RawBytes::default(), RawBytes::default()).unwrap();
+        hook.call(&nft.runtime).unwrap();
+        snapshot.record("transfer", prices.take_estimate(&nft.runtime));
+
+        nft.burn(&ALICE, &[1]).unwrap();
+        snapshot.record("burn", prices.take_estimate(&nft.runtime));
+
+        snapshot.assert_matches();
+    }
 }
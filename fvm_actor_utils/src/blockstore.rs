@@ -2,7 +2,7 @@ use anyhow::anyhow;
 use anyhow::Result;
 use cid::multihash::Code;
 use cid::Cid;
-use fvm_ipld_blockstore::Block;
+use fvm_ipld_blockstore::{Block, MemoryBlockstore};
 use fvm_sdk::ipld;
 
 /// A blockstore that delegates to IPLD syscalls.
@@ -30,11 +30,101 @@ impl fvm_ipld_blockstore::Blockstore for Blockstore {
     where
         D: AsRef<[u8]>,
     {
-        // TODO: Don't hard-code the size. Unfortunately, there's no good way to get it from the
-        //  codec at the moment.
-        const SIZE: u32 = 32;
-        let k = ipld::put(code.into(), SIZE, block.codec, block.data.as_ref())
+        let size = digest_size(code, block.data.as_ref())?;
+        let k = ipld::put(code.into(), size, block.codec, block.data.as_ref())
             .map_err(|e| anyhow!("put failed with {:?}", e))?;
         Ok(k)
     }
 }
+
+/// A blockstore that takes part in committing a new state root
+///
+/// [`ActorRuntime::set_root`](crate::util::ActorRuntime::set_root) calls `commit` before setting
+/// the root, so write-back caches such as
+/// [`BufferedBlockstore`](crate::buffered_blockstore::BufferedBlockstore) can persist the blocks
+/// the new state needs. Blockstores that write through keep the default, which does nothing.
+pub trait CommitBlockstore: fvm_ipld_blockstore::Blockstore {
+    /// Makes the blocks reachable from `root` durable
+    fn commit(&self, root: &Cid) -> Result<()> {
+        let _ = root;
+        Ok(())
+    }
+}
+
+impl CommitBlockstore for Blockstore {}
+
+impl CommitBlockstore for MemoryBlockstore {}
+
+/// Multicodec of the identity "hash", whose digest is the data itself
+const IDENTITY_HASH: u64 = 0x00;
+
+/// Length of the digest `code` produces for `data`, in bytes
+///
+/// Sizes are looked up from the multicodec table rather than by hashing, as the FVM needs them
+/// for every put.
+fn digest_size(code: Code, data: &[u8]) -> Result<u32> {
+    let size = match u64::from(code) {
+        IDENTITY_HASH => return Ok(data.len() as u32),
+        // sha2-256, sha2-512
+        0x12 => 32,
+        0x13 => 64,
+        // sha3-512, sha3-384, sha3-256, sha3-224
+        0x14 => 64,
+        0x15 => 48,
+        0x16 => 32,
+        0x17 => 28,
+        // keccak-224, keccak-256, keccak-384, keccak-512
+        0x1a => 28,
+        0x1b => 32,
+        0x1c => 48,
+        0x1d => 64,
+        // blake2b-256, blake2b-512
+        0xb220 => 32,
+        0xb240 => 64,
+        // blake2s-128, blake2s-256
+        0xb250 => 16,
+        0xb260 => 32,
+        // ripemd-160, ripemd-256, ripemd-320
+        0x1053 => 20,
+        0x1054 => 32,
+        0x1055 => 40,
+        code => return Err(anyhow!("unsupported multihash code {code:#x}")),
+    };
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use cid::multihash::{Code, MultihashDigest};
+
+    use super::digest_size;
+
+    #[test]
+    fn derives_digest_size_from_code() {
+        assert_eq!(digest_size(Code::Blake2b256, b"data").unwrap(), 32);
+        assert_eq!(digest_size(Code::Sha2_256, b"data").unwrap(), 32);
+        assert_eq!(digest_size(Code::Keccak256, b"data").unwrap(), 32);
+        assert_eq!(digest_size(Code::Blake2b512, b"data").unwrap(), 64);
+        assert_eq!(digest_size(Code::Ripemd160, b"data").unwrap(), 20);
+        // the table agrees with the hashes themselves
+        for code in [
+            Code::Sha2_256,
+            Code::Sha2_512,
+            Code::Sha3_224,
+            Code::Sha3_256,
+            Code::Sha3_384,
+            Code::Sha3_512,
+            Code::Keccak224,
+            Code::Keccak256,
+            Code::Keccak384,
+            Code::Keccak512,
+            Code::Blake2b256,
+            Code::Blake2b512,
+            Code::Ripemd160,
+            Code::Ripemd256,
+            Code::Ripemd320,
+        ] {
+            assert_eq!(digest_size(code, b"data").unwrap(), code.digest(b"data").size() as u32);
+        }
+    }
+}
@@ -0,0 +1,378 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, Result};
+use cid::multihash::Code;
+use cid::Cid;
+use fvm_ipld_blockstore::{Block, Blockstore};
+use fvm_ipld_encoding::DAG_CBOR;
+
+use crate::blockstore::CommitBlockstore;
+
+/// A write-back cache in front of another blockstore
+///
+/// Puts are held in memory and reads of buffered blocks never reach the underlying store.
+/// Nothing is written until [`BufferedBlockstore::flush`] is called with the new state root, at
+/// which point only the blocks reachable from that root are written and the rest are discarded.
+/// [`ActorRuntime::set_root`](crate::util::ActorRuntime::set_root) flushes automatically, so the
+/// blocks are persisted before any message sent after it can re-enter the actor and read them.
+/// This saves the cost of persisting intermediate nodes, e.g. HAMT nodes that are rewritten
+/// several times during a single method.
+///
+/// Each block is hashed once when it is put, and flushing writes it under that CID. On the FVM
+/// the IPLD syscall hashes the flushed blocks again, so buffering only pays off when blocks are
+/// rewritten or discarded before the root is set.
+#[derive(Debug)]
+pub struct BufferedBlockstore<BS: Blockstore> {
+    store: BS,
+    buffer: RefCell<HashMap<Cid, Vec<u8>>>,
+}
+
+impl<BS: Blockstore> BufferedBlockstore<BS> {
+    pub fn new(store: BS) -> Self {
+        Self { store, buffer: RefCell::new(HashMap::new()) }
+    }
+
+    /// The blockstore that buffered blocks are flushed to
+    pub fn inner(&self) -> &BS {
+        &self.store
+    }
+
+    /// Consumes the buffer, discarding any blocks that haven't been flushed
+    pub fn into_inner(self) -> BS {
+        self.store
+    }
+
+    /// Number of blocks waiting to be flushed
+    pub fn buffered_len(&self) -> usize {
+        self.buffer.borrow().len()
+    }
+
+    /// Writes the buffered blocks reachable from `root` to the underlying store and discards the
+    /// rest
+    ///
+    /// Blocks are written children first, so every block's links already exist in the underlying
+    /// store when it is written. Links to blocks outside the buffer are assumed to be persisted
+    /// already and are not followed. Blocks stay buffered until they have been written, so if a
+    /// write fails the flush can be retried without losing any of them.
+    pub fn flush(&self, root: &Cid) -> Result<()> {
+        let mut buffer = self.buffer.borrow_mut();
+        let mut visited = HashSet::new();
+        // (cid, whether its links have been pushed already)
+        let mut stack = vec![(*root, false)];
+        while let Some((cid, expanded)) = stack.pop() {
+            if expanded {
+                let block = buffer.get(&cid).expect("expanded blocks are still buffered");
+                self.store.put_keyed(&cid, block)?;
+                buffer.remove(&cid);
+                continue;
+            }
+            if !visited.insert(cid) {
+                continue;
+            }
+            let Some(block) = buffer.get(&cid) else {
+                continue;
+            };
+            stack.push((cid, true));
+            if cid.codec() == DAG_CBOR {
+                for link in scan_links(block)? {
+                    stack.push((link, false));
+                }
+            }
+        }
+        buffer.clear();
+        Ok(())
+    }
+
+    /// Drops all buffered blocks without writing them
+    pub fn discard(&self) {
+        self.buffer.borrow_mut().clear();
+    }
+}
+
+impl<BS: CommitBlockstore> CommitBlockstore for BufferedBlockstore<BS> {
+    fn commit(&self, root: &Cid) -> Result<()> {
+        self.flush(root)?;
+        self.store.commit(root)
+    }
+}
+
+impl<BS: Blockstore> Blockstore for BufferedBlockstore<BS> {
+    fn get(&self, k: &Cid) -> Result<Option<Vec<u8>>> {
+        if let Some(block) = self.buffer.borrow().get(k) {
+            return Ok(Some(block.clone()));
+        }
+        self.store.get(k)
+    }
+
+    fn put_keyed(&self, k: &Cid, block: &[u8]) -> Result<()> {
+        self.buffer.borrow_mut().insert(*k, block.to_vec());
+        Ok(())
+    }
+
+    fn put<D>(&self, code: Code, block: &Block<D>) -> Result<Cid>
+    where
+        D: AsRef<[u8]>,
+    {
+        let k = block.cid(code);
+        self.buffer.borrow_mut().entry(k).or_insert_with(|| block.data.as_ref().to_vec());
+        Ok(k)
+    }
+
+    fn has(&self, k: &Cid) -> Result<bool> {
+        if self.buffer.borrow().contains_key(k) {
+            return Ok(true);
+        }
+        self.store.has(k)
+    }
+}
+
+/// CBOR tag marking a CID in DAG-CBOR
+const CID_TAG: u64 = 42;
+
+/// Collects the CIDs linked from a DAG-CBOR block without decoding the rest of it
+fn scan_links(block: &[u8]) -> Result<Vec<Cid>> {
+    let mut reader = CborReader { data: block, offset: 0 };
+    let mut links = Vec::new();
+    // number of data items left to read
+    let mut remaining: u64 = 1;
+    while remaining > 0 {
+        remaining -= 1;
+        let (major, value) = reader.header()?;
+        match major {
+            // byte and text strings
+            2 | 3 => {
+                reader.bytes(value)?;
+            }
+            // arrays and maps
+            4 => remaining = add_items(remaining, value)?,
+            5 => remaining = add_items(remaining, value.checked_mul(2).ok_or_else(too_long)?)?,
+            // tags apply to the item that follows
+            6 if value == CID_TAG => {
+                let (major, len) = reader.header()?;
+                if major != 2 {
+                    return Err(anyhow!("CID tag must wrap a byte string"));
+                }
+                // DAG-CBOR prefixes CIDs with the identity multibase
+                let bytes = match reader.bytes(len)? {
+                    [0x00, cid @ ..] => cid,
+                    _ => return Err(anyhow!("CID is missing the identity multibase prefix")),
+                };
+                links.push(Cid::try_from(bytes)?);
+            }
+            6 => remaining = add_items(remaining, 1)?,
+            // integers, floats and simple values
+            _ => {}
+        }
+    }
+    Ok(links)
+}
+
+fn add_items(remaining: u64, items: u64) -> Result<u64> {
+    remaining.checked_add(items).ok_or_else(too_long)
+}
+
+fn too_long() -> anyhow::Error {
+    anyhow!("CBOR item count overflows")
+}
+
+struct CborReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> CborReader<'a> {
+    fn bytes(&mut self, len: u64) -> Result<&'a [u8]> {
+        let end = usize::try_from(len)
+            .ok()
+            .and_then(|len| self.offset.checked_add(len))
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| anyhow!("unexpected end of CBOR block"))?;
+        let bytes = &self.data[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    /// Reads an item header, returning its major type and argument
+    fn header(&mut self) -> Result<(u8, u64)> {
+        let byte = self.bytes(1)?[0];
+        let (major, info) = (byte >> 5, byte & 0x1f);
+        let value = match info {
+            0..=23 => info as u64,
+            24 => self.bytes(1)?[0] as u64,
+            25 => u16::from_be_bytes(self.bytes(2)?.try_into()?) as u64,
+            26 => u32::from_be_bytes(self.bytes(4)?.try_into()?) as u64,
+            27 => u64::from_be_bytes(self.bytes(8)?.try_into()?),
+            _ => return Err(anyhow!("invalid DAG-CBOR item header {byte:#04x}")),
+        };
+        Ok((major, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cid::multihash::Code;
+    use fvm_ipld_blockstore::{Blockstore, MemoryBlockstore};
+    use fvm_ipld_encoding::{CborStore, IPLD_RAW};
+
+    use super::{scan_links, BufferedBlockstore};
+    use crate::gas::PriceList;
+    use crate::syscalls::fake_syscalls::FakeSyscalls;
+    use crate::tracking_blockstore::{BlockstoreStats, TrackingBlockstore};
+    use crate::util::ActorRuntime;
+
+    #[test]
+    fn flushes_reachable_blocks() {
+        let bs = BufferedBlockstore::new(MemoryBlockstore::new());
+        let leaf = bs.put_cbor(&"leaf", Code::Blake2b256).unwrap();
+        let shared = bs.put_cbor(&(1u64, leaf), Code::Blake2b256).unwrap();
+        let root = bs.put_cbor(&(vec![shared, leaf], shared), Code::Blake2b256).unwrap();
+        let garbage = bs.put_cbor(&(2u64, leaf), Code::Blake2b256).unwrap();
+        assert_eq!(bs.buffered_len(), 4);
+
+        // buffered blocks are readable before they are written
+        assert!(bs.has(&garbage).unwrap());
+        assert!(!bs.inner().has(&root).unwrap());
+        assert_eq!(bs.get_cbor::<String>(&leaf).unwrap().unwrap(), "leaf");
+
+        bs.flush(&root).unwrap();
+        assert_eq!(bs.buffered_len(), 0);
+        for cid in [root, shared, leaf] {
+            assert!(bs.inner().has(&cid).unwrap());
+        }
+        assert!(!bs.has(&garbage).unwrap());
+    }
+
+    #[test]
+    fn writes_children_first() {
+        #[derive(Default)]
+        struct CheckedStore(MemoryBlockstore);
+
+        impl Blockstore for CheckedStore {
+            fn get(&self, k: &cid::Cid) -> anyhow::Result<Option<Vec<u8>>> {
+                self.0.get(k)
+            }
+
+            fn put_keyed(&self, k: &cid::Cid, block: &[u8]) -> anyhow::Result<()> {
+                for link in scan_links(block)? {
+                    assert!(self.0.has(&link)?, "{k} written before its link {link}");
+                }
+                self.0.put_keyed(k, block)
+            }
+        }
+
+        let bs = BufferedBlockstore::new(CheckedStore::default());
+        let leaf = bs.put_cbor(&"leaf", Code::Blake2b256).unwrap();
+        let middle = bs.put_cbor(&(leaf,), Code::Blake2b256).unwrap();
+        let root = bs.put_cbor(&(leaf, middle, leaf), Code::Blake2b256).unwrap();
+        bs.flush(&root).unwrap();
+        assert!(bs.inner().0.has(&root).unwrap());
+    }
+
+    #[test]
+    fn keeps_unwritten_blocks_when_a_write_fails() {
+        #[derive(Default)]
+        struct FailingStore {
+            store: MemoryBlockstore,
+            fail_on: std::cell::Cell<Option<cid::Cid>>,
+        }
+
+        impl Blockstore for FailingStore {
+            fn get(&self, k: &cid::Cid) -> anyhow::Result<Option<Vec<u8>>> {
+                self.store.get(k)
+            }
+
+            fn put_keyed(&self, k: &cid::Cid, block: &[u8]) -> anyhow::Result<()> {
+                if self.fail_on.get() == Some(*k) {
+                    anyhow::bail!("failed to write {k}");
+                }
+                self.store.put_keyed(k, block)
+            }
+        }
+
+        let bs = BufferedBlockstore::new(FailingStore::default());
+        let leaf = bs.put_cbor(&"leaf", Code::Blake2b256).unwrap();
+        let middle = bs.put_cbor(&(leaf,), Code::Blake2b256).unwrap();
+        let root = bs.put_cbor(&(middle, leaf), Code::Blake2b256).unwrap();
+
+        bs.inner().fail_on.set(Some(middle));
+        bs.flush(&root).unwrap_err();
+        assert!(bs.inner().store.has(&leaf).unwrap());
+        assert_eq!(bs.buffered_len(), 2);
+
+        // retrying writes the blocks that were left
+        bs.inner().fail_on.set(None);
+        bs.flush(&root).unwrap();
+        assert_eq!(bs.buffered_len(), 0);
+        for cid in [root, middle, leaf] {
+            assert!(bs.inner().store.has(&cid).unwrap());
+        }
+    }
+
+    #[test]
+    fn writes_less_than_an_unbuffered_store() {
+        // rewrites the same node on every insert, like a HAMT
+        fn insert_all<BS: Blockstore>(bs: &BS) -> cid::Cid {
+            let mut leaves = Vec::new();
+            let mut root = None;
+            for value in 0..10u64 {
+                leaves.push(bs.put_cbor(&(value, "leaf"), Code::Blake2b256).unwrap());
+                let node = bs.put_cbor(&leaves, Code::Blake2b256).unwrap();
+                root = Some(bs.put_cbor(&(node, value), Code::Blake2b256).unwrap());
+            }
+            root.unwrap()
+        }
+
+        let prices = PriceList::default();
+        let unbuffered = TrackingBlockstore::new(MemoryBlockstore::new());
+        let expected = insert_all(&unbuffered);
+        let unbuffered = unbuffered.stats();
+
+        let bs = BufferedBlockstore::new(TrackingBlockstore::new(MemoryBlockstore::new()));
+        let root = insert_all(&bs);
+        assert_eq!(root, expected);
+        assert_eq!(bs.inner().stats().puts, 0);
+        bs.flush(&root).unwrap();
+        let buffered = bs.inner().stats();
+
+        // only the leaves and the last node and root are written
+        assert_eq!(unbuffered.puts, 30);
+        assert_eq!(buffered.puts, 12);
+        let hashing =
+            |stats: BlockstoreStats| prices.blake2b256.apply(stats.puts, stats.bytes_written);
+        assert!(hashing(buffered) < hashing(unbuffered));
+        assert!(prices.on_ipld(&buffered).total() < prices.on_ipld(&unbuffered).total());
+    }
+
+    #[test]
+    fn skips_persisted_and_raw_blocks() {
+        let bs = BufferedBlockstore::new(MemoryBlockstore::new());
+        let persisted = bs.inner().put_cbor(&"persisted", Code::Blake2b256).unwrap();
+        let raw = bs
+            .put(Code::Blake2b256, &fvm_ipld_blockstore::Block::new(IPLD_RAW, b"raw".as_slice()))
+            .unwrap();
+        let root = bs.put_cbor(&(persisted, raw), Code::Blake2b256).unwrap();
+        bs.flush(&root).unwrap();
+        assert!(bs.inner().has(&raw).unwrap());
+        assert!(bs.inner().has(&root).unwrap());
+
+        bs.put_cbor(&"discarded", Code::Blake2b256).unwrap();
+        bs.discard();
+        assert_eq!(bs.buffered_len(), 0);
+    }
+
+    #[test]
+    fn flushes_when_the_root_is_set() {
+        let runtime = ActorRuntime::new(
+            FakeSyscalls::default(),
+            BufferedBlockstore::new(MemoryBlockstore::new()),
+        );
+        let leaf = runtime.put_cbor(&"leaf", Code::Blake2b256).unwrap();
+        let root = runtime.put_cbor(&(leaf,), Code::Blake2b256).unwrap();
+
+        runtime.set_root(&root).unwrap();
+        assert_eq!(runtime.root_cid().unwrap(), root);
+        assert_eq!(runtime.bs().buffered_len(), 0);
+        assert!(runtime.bs().inner().has(&leaf).unwrap());
+    }
+}
@@ -0,0 +1,87 @@
+/// Declares a typed client for calling the exported methods of another actor
+///
+/// Each method is declared with its FRC-0042 name in a `#[method]` attribute, in the same way as
+/// the `frc42_dispatch::actor` attribute, and takes at most one param. The generated struct wraps
+/// the address of the remote actor and a `&dyn Messaging`, so it works with the on-chain
+/// `FvmMessenger` or an `ActorRuntime` built on `FakeSyscalls` in tests. Calling a
+/// method serializes its param, sends the message with no value attached, fails with
+/// `MessagingError::Aborted` if the remote actor exits with a non-zero code and decodes the return.
+///
+/// ```ignore
+/// use fvm_actor_utils::client;
+///
+/// client! {
+///     /// Calls the greeter example actor
+///     pub struct GreeterClient {
+///         #[method("Greet")]
+///         fn greet(name: String) -> String;
+///     }
+/// }
+///
+/// let greeter = GreeterClient::new(&runtime, greeter_address);
+/// let greeting = greeter.greet("World".into())?;
+/// ```
+#[macro_export]
+macro_rules! client {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $client:ident {
+            $(
+                $(#[doc = $doc:literal])*
+                #[method($name:literal)]
+                fn $fn:ident($($param:ident : $params:ty)?) $(-> $returns:ty)?;
+            )*
+        }
+    ) => {
+        $crate::__private::check_method_names!($($name),*);
+
+        $(#[$attr])*
+        $vis struct $client<'a> {
+            address: $crate::__private::Address,
+            messaging: &'a dyn $crate::messaging::Messaging,
+        }
+
+        impl<'a> $client<'a> {
+            /// Creates a client for the actor at `address`, sending messages through `messaging`
+            pub fn new(
+                messaging: &'a dyn $crate::messaging::Messaging,
+                address: $crate::__private::Address,
+            ) -> Self {
+                Self { address, messaging }
+            }
+
+            /// The address of the remote actor
+            pub fn address(&self) -> &$crate::__private::Address {
+                &self.address
+            }
+
+            $(
+                $(#[doc = $doc])*
+                pub fn $fn(
+                    &self
+                    $(, $param: $params)?
+                ) -> $crate::messaging::Result<$crate::client!(@returns $($returns)?)> {
+                    let method = $crate::__private::method_hash!($name);
+                    let params = $crate::client!(@params $($param)?);
+                    let response = self.messaging.send(
+                        &self.address,
+                        method,
+                        params,
+                        $crate::__private::TokenAmount::default(),
+                    )?;
+                    $crate::client!(@decode &self.address, method, response, $($returns)?)
+                }
+            )*
+        }
+    };
+    (@returns) => { () };
+    (@returns $returns:ty) => { $returns };
+    (@params) => { ::std::option::Option::None };
+    (@params $param:ident) => { $crate::__private::IpldBlock::serialize_cbor(&$param)? };
+    (@decode $to:expr, $method:expr, $response:expr,) => {
+        $crate::messaging::check_exit_code($to, $method, $response).map(|_| ())
+    };
+    (@decode $to:expr, $method:expr, $response:expr, $returns:ty) => {
+        $crate::messaging::decode_return::<$returns>($to, $method, $response)
+    };
+}
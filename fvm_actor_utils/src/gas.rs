@@ -0,0 +1,337 @@
+//! Offline gas estimates for library operations
+//!
+//! A [`PriceList`] approximates the charges the FVM makes for the syscalls recorded by
+//! [`FakeSyscalls`] and a [`TrackingBlockstore`]: sends, IPLD reads and writes, and hashing. This
+//! lets token and NFT operations be costed in plain unit tests. The execution of wasm code and of
+//! any actors that were called is not modelled, so estimates are only useful for comparing
+//! operations and spotting regressions, e.g. with a [`GasSnapshot`].
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::{Add, AddAssign};
+use std::path::PathBuf;
+
+use fvm_ipld_blockstore::Blockstore;
+use fvm_shared::crypto::hash::SupportedHashes;
+use fvm_shared::METHOD_SEND;
+
+use crate::syscalls::fake_syscalls::{FakeSyscalls, SentMessage};
+use crate::tracking_blockstore::{BlockstoreStats, TrackingBlockstore};
+use crate::util::ActorRuntime;
+
+/// Environment variable that makes [`GasSnapshot::assert_matches`] overwrite outdated snapshots
+pub const UPDATE_GAS_SNAPSHOTS: &str = "UPDATE_GAS_SNAPSHOTS";
+
+/// A charge made up of a flat fee plus a fee per byte
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScalingCost {
+    pub flat: u64,
+    pub per_byte: u64,
+}
+
+impl ScalingCost {
+    /// Cost of `count` operations over `bytes` bytes in total
+    pub const fn apply(&self, count: u64, bytes: u64) -> u64 {
+        self.flat * count + self.per_byte * bytes
+    }
+}
+
+/// Approximate gas prices of the syscalls made by actors
+///
+/// The defaults are rounded from the FVM v3 price list and can be adjusted to model other network
+/// versions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PriceList {
+    /// Charged for every syscall, on top of its own cost
+    pub syscall: u64,
+    /// Charged for every message sent
+    pub send_base: u64,
+    /// Charged for sends carrying a non-zero value
+    pub send_transfer_funds: u64,
+    /// Charged for sends invoking a method other than [`METHOD_SEND`]
+    pub send_invoke_method: u64,
+    /// Charged for reading a block
+    pub block_get: ScalingCost,
+    /// Charged for writing a block, not including computing its CID
+    pub block_put: ScalingCost,
+    pub sha2_256: ScalingCost,
+    pub blake2b256: ScalingCost,
+    pub blake2b512: ScalingCost,
+    pub keccak256: ScalingCost,
+    pub ripemd160: ScalingCost,
+}
+
+impl Default for PriceList {
+    fn default() -> Self {
+        Self {
+            syscall: 14_000,
+            send_base: 29_233,
+            send_transfer_funds: 27_500,
+            send_invoke_method: 500_000,
+            block_get: ScalingCost { flat: 187_440, per_byte: 10 },
+            block_put: ScalingCost { flat: 353_640, per_byte: 1_300 },
+            sha2_256: ScalingCost { flat: 0, per_byte: 7 },
+            blake2b256: ScalingCost { flat: 10, per_byte: 10 },
+            blake2b512: ScalingCost { flat: 10, per_byte: 10 },
+            keccak256: ScalingCost { flat: 2_000, per_byte: 25 },
+            ripemd160: ScalingCost { flat: 1_000, per_byte: 20 },
+        }
+    }
+}
+
+impl PriceList {
+    /// Price of computing a digest with `hasher`
+    pub fn hashing(&self, hasher: SupportedHashes) -> ScalingCost {
+        match hasher {
+            SupportedHashes::Sha2_256 => self.sha2_256,
+            SupportedHashes::Blake2b256 => self.blake2b256,
+            SupportedHashes::Blake2b512 => self.blake2b512,
+            SupportedHashes::Keccak256 => self.keccak256,
+            SupportedHashes::Ripemd160 => self.ripemd160,
+        }
+    }
+
+    /// Gas charged to the sender of a message, excluding the execution of the receiver
+    pub fn on_send(&self, message: &SentMessage) -> u64 {
+        let mut gas = self.syscall + self.send_base;
+        if !message.value.is_zero() {
+            gas += self.send_transfer_funds;
+        }
+        if message.method != METHOD_SEND {
+            gas += self.send_invoke_method;
+        }
+        gas
+    }
+
+    /// Gas charged for the IPLD traffic recorded by a [`TrackingBlockstore`]
+    ///
+    /// Written blocks are also charged for hashing them into their CID.
+    pub fn on_ipld(&self, stats: &BlockstoreStats) -> GasEstimate {
+        let ipld_reads =
+            self.block_get.apply(stats.gets, stats.bytes_read) + self.syscall * stats.gets;
+        let ipld_writes = self.block_put.apply(stats.puts, stats.bytes_written)
+            + self.blake2b256.apply(stats.puts, stats.bytes_written)
+            + self.syscall * stats.puts;
+        GasEstimate { ipld_reads, ipld_writes, ..Default::default() }
+    }
+
+    /// Gas charged for hashing `len` bytes with `hasher`
+    pub fn on_hash(&self, hasher: SupportedHashes, len: usize) -> u64 {
+        self.syscall + self.hashing(hasher).apply(1, len as u64)
+    }
+
+    /// Estimates the gas of the operations recorded since the last call, clearing the records
+    pub fn take_estimate<BS: Blockstore>(
+        &self,
+        runtime: &ActorRuntime<FakeSyscalls, TrackingBlockstore<BS>>,
+    ) -> GasEstimate {
+        let sends = runtime.syscalls.take_sent_messages().iter().map(|m| self.on_send(m)).sum();
+        let hashing = runtime
+            .syscalls
+            .take_hashes()
+            .into_iter()
+            .map(|(hasher, len)| self.on_hash(hasher, len))
+            .sum();
+        GasEstimate { sends, hashing, ..Default::default() }
+            + self.on_ipld(&runtime.blockstore.take_stats())
+    }
+}
+
+/// Estimated gas, broken down by the kind of operation it was charged for
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GasEstimate {
+    pub sends: u64,
+    pub ipld_reads: u64,
+    pub ipld_writes: u64,
+    pub hashing: u64,
+}
+
+impl GasEstimate {
+    pub fn total(&self) -> u64 {
+        self.sends + self.ipld_reads + self.ipld_writes + self.hashing
+    }
+}
+
+impl Add for GasEstimate {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            sends: self.sends + rhs.sends,
+            ipld_reads: self.ipld_reads + rhs.ipld_reads,
+            ipld_writes: self.ipld_writes + rhs.ipld_writes,
+            hashing: self.hashing + rhs.hashing,
+        }
+    }
+}
+
+impl AddAssign for GasEstimate {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl fmt::Display for GasEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "total={} sends={} ipld_reads={} ipld_writes={} hashing={}",
+            self.total(),
+            self.sends,
+            self.ipld_reads,
+            self.ipld_writes,
+            self.hashing
+        )
+    }
+}
+
+/// Named gas estimates, checked against a snapshot file so that changes show up in review
+///
+/// The file is only written when the [`UPDATE_GAS_SNAPSHOTS`] environment variable is set, so a
+/// missing snapshot fails like an outdated one instead of being created by a plain test run.
+#[derive(Clone, Debug)]
+pub struct GasSnapshot {
+    path: PathBuf,
+    entries: BTreeMap<String, GasEstimate>,
+}
+
+impl GasSnapshot {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), entries: BTreeMap::new() }
+    }
+
+    /// Records the estimate for an operation, replacing any previous one of the same name
+    pub fn record(&mut self, name: &str, estimate: GasEstimate) {
+        assert!(!name.contains(['\n', ':']), "invalid gas snapshot entry name {name:?}");
+        self.entries.insert(name.to_owned(), estimate);
+    }
+
+    /// The snapshot file contents for the recorded estimates
+    pub fn render(&self) -> String {
+        self.entries.iter().map(|(name, estimate)| format!("{name}: {estimate}\n")).collect()
+    }
+
+    /// Panics if the recorded estimates differ from the snapshot file, listing the changes
+    pub fn assert_matches(&self) {
+        if let Err(message) = self.check(std::env::var_os(UPDATE_GAS_SNAPSHOTS).is_some()) {
+            panic!("{message}");
+        }
+    }
+
+    fn check(&self, update: bool) -> Result<(), String> {
+        let rendered = self.render();
+        let existing = match std::fs::read_to_string(&self.path) {
+            Ok(existing) if existing == rendered => return Ok(()),
+            _ if update => {
+                return std::fs::write(&self.path, rendered).map_err(|e| {
+                    format!("failed to write gas snapshot {}: {e}", self.path.display())
+                })
+            }
+            Ok(existing) => existing,
+            Err(e) => {
+                return Err(format!(
+                    "failed to read gas snapshot {}: {e}, \
+                     run with {UPDATE_GAS_SNAPSHOTS}=1 to create it",
+                    self.path.display()
+                ))
+            }
+        };
+
+        let diff: String = existing
+            .lines()
+            .filter(|line| !rendered.lines().any(|l| l == *line))
+            .map(|line| format!("- {line}\n"))
+            .chain(
+                rendered
+                    .lines()
+                    .filter(|line| !existing.lines().any(|l| l == *line))
+                    .map(|line| format!("+ {line}\n")),
+            )
+            .collect();
+        Err(format!(
+            "gas snapshot {} is out of date, rerun with {UPDATE_GAS_SNAPSHOTS}=1 to update it:\n{diff}",
+            self.path.display()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cid::multihash::Code;
+    use fvm_ipld_blockstore::MemoryBlockstore;
+    use fvm_ipld_encoding::CborStore;
+    use fvm_shared::address::Address;
+    use fvm_shared::crypto::hash::SupportedHashes;
+    use fvm_shared::econ::TokenAmount;
+    use fvm_shared::METHOD_SEND;
+
+    use super::{GasEstimate, GasSnapshot, PriceList};
+    use crate::syscalls::fake_syscalls::FakeSyscalls;
+    use crate::syscalls::Syscalls;
+    use crate::util::ActorRuntime;
+
+    #[test]
+    fn estimates_recorded_operations() {
+        let prices = PriceList::default();
+        let runtime = ActorRuntime::<FakeSyscalls, MemoryBlockstore>::new_tracking_test_runtime();
+        runtime.syscalls.set_balance(TokenAmount::from_atto(100));
+        runtime
+            .syscalls
+            .send(&Address::new_id(10), METHOD_SEND, None, TokenAmount::from_atto(1))
+            .unwrap();
+        runtime.syscalls.send(&Address::new_id(10), 42, None, TokenAmount::default()).unwrap();
+        runtime.syscalls.hash(SupportedHashes::Keccak256, &[0; 32]);
+        let cid = runtime.put_cbor(&"block", Code::Blake2b256).unwrap();
+        runtime.get_cbor::<String>(&cid).unwrap();
+
+        let stats = runtime.blockstore.stats();
+        let estimate = prices.take_estimate(&runtime);
+        let send_base = prices.syscall + prices.send_base;
+        assert_eq!(
+            estimate.sends,
+            2 * send_base + prices.send_transfer_funds + prices.send_invoke_method
+        );
+        assert_eq!(estimate.hashing, prices.syscall + prices.keccak256.apply(1, 32));
+        assert_eq!(
+            GasEstimate { sends: estimate.sends, hashing: estimate.hashing, ..Default::default() }
+                + prices.on_ipld(&stats),
+            estimate
+        );
+        assert!(estimate.ipld_writes > estimate.ipld_reads);
+
+        // the records were cleared
+        assert_eq!(prices.take_estimate(&runtime), GasEstimate::default());
+    }
+
+    #[test]
+    fn checks_snapshots() {
+        let path = std::env::temp_dir().join(format!("gas-snapshot-{}.txt", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut snapshot = GasSnapshot::new(&path);
+        snapshot.record("transfer", GasEstimate { sends: 1, ..Default::default() });
+        snapshot.record("mint", GasEstimate { ipld_writes: 2, ..Default::default() });
+
+        // a missing snapshot fails unless updating, which creates it
+        let message = snapshot.check(false).unwrap_err();
+        assert!(message.contains("UPDATE_GAS_SNAPSHOTS=1 to create it"));
+        assert!(!path.exists());
+        snapshot.check(true).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "mint: total=2 sends=0 ipld_reads=0 ipld_writes=2 hashing=0\n\
+             transfer: total=1 sends=1 ipld_reads=0 ipld_writes=0 hashing=0\n"
+        );
+        snapshot.check(false).unwrap();
+
+        snapshot.record("mint", GasEstimate { ipld_writes: 3, ..Default::default() });
+        let message = snapshot.check(false).unwrap_err();
+        assert!(message.contains("- mint: total=2"));
+        assert!(message.contains("+ mint: total=3"));
+        assert!(!message.contains("transfer"));
+
+        // or overwritten when updating
+        snapshot.check(true).unwrap();
+        snapshot.check(false).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,8 +1,21 @@
 pub mod actor;
 pub mod blockstore;
+pub mod buffered_blockstore;
+pub mod client;
+pub mod gas;
 pub mod messaging;
 pub mod receiver;
 
 pub mod shared_blockstore;
+pub mod solidity;
 pub mod syscalls;
+pub mod tracking_blockstore;
 pub mod util;
+
+/// Items used by the code generated by [`client!`]
+#[doc(hidden)]
+pub mod __private {
+    pub use frc42_dispatch::{check_method_names, method_hash};
+    pub use fvm_ipld_encoding::ipld_block::IpldBlock;
+    pub use fvm_shared::{address::Address, econ::TokenAmount};
+}
@@ -3,14 +3,17 @@ use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_ipld_encoding::Error as IpldError;
 use fvm_sdk::{send, sys::ErrorNumber};
 use fvm_shared::error::ExitCode;
-use fvm_shared::sys::SendFlags;
 use fvm_shared::{address::Address, econ::TokenAmount};
 use fvm_shared::{MethodNum, Response};
+use serde::de::DeserializeOwned;
 use thiserror::Error;
 
+pub use frc42_dispatch::message::SendOptions;
+
 pub type Result<T> = std::result::Result<T, MessagingError>;
 
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum MessagingError {
     #[error("fvm syscall error: `{0}`")]
     Syscall(#[from] ErrorNumber),
@@ -20,6 +23,10 @@ pub enum MessagingError {
     AddressNotInitialized(Address),
     #[error("ipld serialization error: `{0}`")]
     Ipld(#[from] IpldError),
+    #[error("method `{method}` on `{to}` aborted with exit code `{exit_code}`")]
+    Aborted { to: Address, method: MethodNum, exit_code: ExitCode },
+    #[error("method `{method}` on `{to}` returned no data")]
+    MissingReturn { to: Address, method: MethodNum },
 }
 
 impl From<&MessagingError> for ExitCode {
@@ -41,7 +48,12 @@ impl From<&MessagingError> for ExitCode {
             MessagingError::AddressNotResolved(_) | MessagingError::AddressNotInitialized(_) => {
                 ExitCode::USR_NOT_FOUND
             }
-            MessagingError::Ipld(_) => ExitCode::USR_SERIALIZATION,
+            MessagingError::Ipld(_) | MessagingError::MissingReturn { .. } => {
+                ExitCode::USR_SERIALIZATION
+            }
+            // actors can't abort with system exit codes, so only user codes are passed on
+            MessagingError::Aborted { exit_code, .. } if !exit_code.is_system_error() => *exit_code,
+            MessagingError::Aborted { .. } => ExitCode::USR_UNSPECIFIED,
         }
     }
 }
@@ -56,6 +68,52 @@ pub trait Messaging {
         params: Option<IpldBlock>,
         value: TokenAmount,
     ) -> Result<Response>;
+
+    /// Sends a message to an actor with a gas limit, read-only or other flags
+    ///
+    /// The default implementation sends messages with default options through `send` and fails
+    /// with `IllegalArgument` for any other options, so implementations that can honour them
+    /// should override it.
+    fn send_with_options(
+        &self,
+        to: &Address,
+        method: MethodNum,
+        params: Option<IpldBlock>,
+        value: TokenAmount,
+        options: SendOptions,
+    ) -> Result<Response> {
+        if options != SendOptions::default() {
+            return Err(ErrorNumber::IllegalArgument.into());
+        }
+        self.send(to, method, params, value)
+    }
+}
+
+/// Checks that a message succeeded, returning its return data
+///
+/// Returns MessagingError::Aborted if the receiving actor exited with a non-zero exit code
+pub fn check_exit_code(
+    to: &Address,
+    method: MethodNum,
+    response: Response,
+) -> Result<Option<IpldBlock>> {
+    if response.exit_code.is_success() {
+        Ok(response.return_data)
+    } else {
+        Err(MessagingError::Aborted { to: *to, method, exit_code: response.exit_code })
+    }
+}
+
+/// Checks that a message succeeded and decodes its return data
+pub fn decode_return<R: DeserializeOwned>(
+    to: &Address,
+    method: MethodNum,
+    response: Response,
+) -> Result<R> {
+    match check_exit_code(to, method, response)? {
+        Some(block) => Ok(block.deserialize()?),
+        None => Err(MessagingError::MissingReturn { to: *to, method }),
+    }
 }
 
 /// This method number comes from taking the name as "Receive" and applying
@@ -73,6 +131,17 @@ impl Messaging for FvmMessenger {
         params: Option<IpldBlock>,
         value: TokenAmount,
     ) -> Result<Response> {
-        Ok(send::send(to, method, params, value, None, SendFlags::empty())?)
+        self.send_with_options(to, method, params, value, SendOptions::default())
+    }
+
+    fn send_with_options(
+        &self,
+        to: &Address,
+        method: MethodNum,
+        params: Option<IpldBlock>,
+        value: TokenAmount,
+        options: SendOptions,
+    ) -> Result<Response> {
+        Ok(send::send(to, method, params, value, options.gas_limit(), options.flags())?)
     }
 }
@@ -1,5 +1,6 @@
 use std::mem;
 
+use frc42_dispatch::interface::{interface_id, InterfaceId};
 use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_ipld_encoding::tuple::{Deserialize_tuple, Serialize_tuple};
 use fvm_ipld_encoding::RawBytes;
@@ -7,7 +8,7 @@ use fvm_shared::{address::Address, econ::TokenAmount, error::ExitCode};
 use num_traits::Zero;
 use thiserror::Error;
 
-use crate::messaging::{Messaging, MessagingError, RECEIVER_HOOK_METHOD_NUM};
+use crate::messaging::{Messaging, MessagingError, SendOptions, RECEIVER_HOOK_METHOD_NUM};
 
 /// Parameters for universal receiver
 ///
@@ -32,6 +33,9 @@ pub trait UniversalReceiver {
     fn receive(params: UniversalReceiverParams);
 }
 
+/// Interface ID of the universal receiver, for answering `SupportsInterface` queries
+pub const UNIVERSAL_RECEIVER_INTERFACE_ID: InterfaceId = interface_id(&["Receive"]);
+
 /// Type of asset received - could be tokens (FRC46 or other) or other assets
 pub type ReceiverType = u32;
 
@@ -124,6 +128,15 @@ impl<T: RecipientData> ReceiverHook<T> {
     /// - an error if the hook call aborted
     /// - any return data provided by the hook upon success
     pub fn call(&mut self, msg: &dyn Messaging) -> std::result::Result<T, ReceiverHookError> {
+        self.call_with_options(msg, SendOptions::default())
+    }
+
+    /// Call the receiver hook with send options, e.g. to limit the gas given to the receiver
+    pub fn call_with_options(
+        &mut self,
+        msg: &dyn Messaging,
+        options: SendOptions,
+    ) -> std::result::Result<T, ReceiverHookError> {
         if self.called {
             return Err(ReceiverHookError::AlreadyCalled);
         }
@@ -135,7 +148,7 @@ impl<T: RecipientData> ReceiverHook<T> {
             payload: mem::take(&mut self.token_params), // once encoded and sent, we don't need this anymore
         };
 
-        let ret = msg.send(
+        let ret = msg.send_with_options(
             &self.address,
             RECEIVER_HOOK_METHOD_NUM,
             IpldBlock::serialize_cbor(&params).map_err(|e| {
@@ -145,6 +158,7 @@ impl<T: RecipientData> ReceiverHook<T> {
                 })
             })?,
             TokenAmount::zero(),
+            options,
         )?;
 
         match ret.exit_code {
@@ -179,14 +193,19 @@ impl<T: RecipientData> std::ops::Drop for ReceiverHook<T> {
 mod test {
     use frc42_dispatch::method_hash;
     use fvm_ipld_blockstore::MemoryBlockstore;
+    use fvm_ipld_encoding::ipld_block::IpldBlock;
     use fvm_ipld_encoding::RawBytes;
     use fvm_shared::address::Address;
+    use fvm_shared::error::ExitCode;
+    use fvm_shared::Response;
 
-    use super::{ReceiverHook, RecipientData};
+    use super::{ReceiverHook, ReceiverHookError, RecipientData};
+    use crate::messaging::{SendOptions, RECEIVER_HOOK_METHOD_NUM};
     use crate::{syscalls::fake_syscalls::FakeSyscalls, util::ActorRuntime};
 
     const ALICE: Address = Address::new_id(2);
 
+    #[derive(Debug)]
     struct TestReturn;
 
     impl RecipientData for TestReturn {
@@ -211,6 +230,37 @@ mod test {
         assert!(util.syscalls.last_message.borrow().is_some());
     }
 
+    #[test]
+    fn calls_hook_with_options() {
+        let mut hook = generate_hook();
+        let util = ActorRuntime::<FakeSyscalls, MemoryBlockstore>::new_test_runtime();
+        let options = SendOptions::new().with_gas_limit(1_000_000);
+        hook.call_with_options(&util, options).unwrap();
+        util.syscalls.expect_send().to(&ALICE).options(options).once();
+    }
+
+    #[test]
+    fn reports_rejection() {
+        let mut hook = generate_hook();
+        let util = ActorRuntime::<FakeSyscalls, MemoryBlockstore>::new_test_runtime();
+        let return_data = IpldBlock::serialize_cbor(&"not accepted").unwrap();
+        util.syscalls.respond_to(
+            ALICE,
+            RECEIVER_HOOK_METHOD_NUM,
+            Ok(Response { exit_code: ExitCode::USR_FORBIDDEN, return_data: return_data.clone() }),
+        );
+
+        let err = hook.call(&util).unwrap_err();
+        assert_eq!(ExitCode::from(&err), ExitCode::USR_FORBIDDEN);
+        match err {
+            ReceiverHookError::Receiver { address, return_data: data, .. } => {
+                assert_eq!(address, ALICE);
+                assert_eq!(data, RawBytes::new(return_data.unwrap().data));
+            }
+            e => panic!("unexpected error {e:?}"),
+        }
+    }
+
     #[test]
     #[should_panic]
     fn panics_if_not_called() {
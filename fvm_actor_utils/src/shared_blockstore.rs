@@ -4,6 +4,8 @@ use anyhow::Result;
 use cid::Cid;
 use fvm_ipld_blockstore::MemoryBlockstore;
 
+use crate::blockstore::CommitBlockstore;
+
 /// A shared wrapper around MemoryBlockstore
 /// Clones of it will reference the same underlying MemoryBlockstore, allowing for more complex unit testing
 #[derive(Debug, Clone)]
@@ -23,6 +25,8 @@ impl Default for SharedMemoryBlockstore {
     }
 }
 
+impl CommitBlockstore for SharedMemoryBlockstore {}
+
 // blockstore implementation, passes calls through to the underlying MemoryBlockstore
 impl fvm_ipld_blockstore::Blockstore for SharedMemoryBlockstore {
     /// Gets the block from the blockstore.
@@ -0,0 +1,79 @@
+//! Helpers for native actors called from, or calling, FEVM contracts
+//!
+//! See [`frc42_dispatch::solidity`] for how Solidity calls are routed to FRC-0042 methods.
+use frc42_dispatch::solidity::{BridgeError, SolidityBridge, INVOKE_EVM_METHOD_NUM};
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_ipld_encoding::{BytesDe, BytesSer};
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::{MethodNum, Response};
+
+use crate::messaging::{Messaging, Result};
+
+/// Translates the params of an `InvokeEVM` message into a call to a native method
+///
+/// The params hold the Solidity calldata as a CBOR byte string. Returns the method number and
+/// params to dispatch, which an actor can pass back into its own dispatch function.
+pub fn translate_invoke_evm(
+    bridge: &SolidityBridge,
+    params: Option<IpldBlock>,
+) -> std::result::Result<(MethodNum, Option<IpldBlock>), BridgeError> {
+    let calldata = match params {
+        Some(params) => params.deserialize::<BytesDe>()?.0,
+        None => Vec::new(),
+    };
+    bridge.translate(&calldata)
+}
+
+/// Sends Solidity calldata to an actor as an `InvokeEVM` message, as a FEVM contract would
+pub fn send_invoke_evm(
+    messaging: &dyn Messaging,
+    to: &Address,
+    calldata: &[u8],
+    value: TokenAmount,
+) -> Result<Response> {
+    let params = IpldBlock::serialize_cbor(&BytesSer(calldata))?;
+    messaging.send(to, INVOKE_EVM_METHOD_NUM, params, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use frc42_dispatch::method_hash;
+    use frc42_dispatch::solidity::{
+        encode_call, AbiValue, BridgeError, ParamsLayout, SolidityBridge, INVOKE_EVM_METHOD_NUM,
+    };
+    use fvm_ipld_blockstore::MemoryBlockstore;
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+
+    use super::{send_invoke_evm, translate_invoke_evm};
+    use crate::syscalls::fake_syscalls::FakeSyscalls;
+    use crate::util::ActorRuntime;
+
+    #[test]
+    fn roundtrips_invoke_evm() {
+        let runtime = ActorRuntime::<FakeSyscalls, MemoryBlockstore>::new_test_runtime();
+        let bridge = SolidityBridge::default()
+            .route("approve(address,uint64)", method_hash!("Approve"), ParamsLayout::Tuple)
+            .unwrap();
+
+        let calldata = encode_call(
+            "approve(address,uint64)",
+            &[AbiValue::Address(Address::new_id(42)), AbiValue::Uint(7)],
+        )
+        .unwrap();
+        send_invoke_evm(&runtime, &Address::new_id(100), &calldata, TokenAmount::default())
+            .unwrap();
+
+        let message = runtime.syscalls.last_message.borrow().clone().unwrap();
+        assert_eq!(message.method, INVOKE_EVM_METHOD_NUM);
+        let (method, params) = translate_invoke_evm(&bridge, message.params).unwrap();
+        assert_eq!(method, method_hash!("Approve"));
+        assert_eq!(
+            params.unwrap().deserialize::<(Address, u64)>().unwrap(),
+            (Address::new_id(42), 7)
+        );
+
+        assert!(matches!(translate_invoke_evm(&bridge, None), Err(BridgeError::Calldata(_))));
+    }
+}
@@ -1,12 +1,20 @@
-use std::{cell::RefCell, collections::HashMap};
+use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
 
 use cid::Cid;
+use frc42_dispatch::message::SendOptions;
+use fvm_ipld_encoding::de::DeserializeOwned;
 use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_ipld_encoding::ser::Serialize;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::crypto::hash::SupportedHashes;
 use fvm_shared::{
-    address::Address, econ::TokenAmount, error::ErrorNumber, error::ExitCode, ActorID, Response,
+    address::Address, econ::TokenAmount, error::ErrorNumber, error::ExitCode, ActorID, MethodNum,
+    Response,
 };
 
 use super::Syscalls;
+use crate::messaging::RECEIVER_HOOK_METHOD_NUM;
+use crate::receiver::{ReceiverType, UniversalReceiverParams};
 
 #[derive(Clone, Default, Debug)]
 pub struct TestMessage {
@@ -15,6 +23,51 @@ pub struct TestMessage {
     pub value: TokenAmount,
 }
 
+/// The result of sending a message
+pub type SendResult = Result<Response, ErrorNumber>;
+
+type RespondFn =
+    dyn Fn(&Address, MethodNum, Option<&IpldBlock>, &TokenAmount) -> Option<SendResult>;
+
+/// Decides the result of messages sent via [`FakeSyscalls`], in place of the default of succeeding
+/// and echoing the params back as return data
+#[derive(Clone)]
+pub struct Responder(Rc<RespondFn>);
+
+impl Responder {
+    /// Responds to the messages for which `respond` returns a result, passing on the others
+    pub fn new<F>(respond: F) -> Self
+    where
+        F: Fn(&Address, MethodNum, Option<&IpldBlock>, &TokenAmount) -> Option<SendResult>
+            + 'static,
+    {
+        Self(Rc::new(respond))
+    }
+
+    /// Responds to every message sent to an address with a method number
+    ///
+    /// The address is compared as given to `send`, without resolving it.
+    pub fn to(address: Address, method: MethodNum, result: SendResult) -> Self {
+        Self::new(move |to, m, _, _| (*to == address && m == method).then(|| result.clone()))
+    }
+
+    fn respond(
+        &self,
+        to: &Address,
+        method: MethodNum,
+        params: Option<&IpldBlock>,
+        value: &TokenAmount,
+    ) -> Option<SendResult> {
+        (self.0)(to, method, params, value)
+    }
+}
+
+impl fmt::Debug for Responder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Responder")
+    }
+}
+
 #[derive(Clone, Default, Debug)]
 pub struct FakeSyscalls {
     /// The root of the receiving actor
@@ -24,6 +77,20 @@ pub struct FakeSyscalls {
 
     /// Actor ID to return as caller ID
     pub caller_id: RefCell<ActorID>,
+    /// Actor ID to return as origin ID
+    pub origin_id: RefCell<ActorID>,
+    /// Method number of the message being handled
+    pub method_num: RefCell<MethodNum>,
+    /// Value received with the message being handled
+    pub value_received: RefCell<TokenAmount>,
+    /// FIL balances of the actors known to this runtime, debited and credited by sends
+    pub balances: RefCell<HashMap<ActorID, TokenAmount>>,
+    /// The current epoch
+    pub epoch: RefCell<ChainEpoch>,
+    /// Gas remaining for the current invocation
+    pub gas_available: RefCell<u64>,
+    /// Code CIDs of the actors known to this runtime
+    pub actor_codes: RefCell<HashMap<ActorID, Cid>>,
 
     /// A map of addresses that were instantiated in this runtime
     pub addresses: RefCell<HashMap<Address, ActorID>>,
@@ -34,6 +101,129 @@ pub struct FakeSyscalls {
     pub last_message: RefCell<Option<TestMessage>>,
     /// Flag to control message success
     pub abort_next_send: RefCell<bool>,
+    /// Responders deciding the result of sent messages, the most recently added taking precedence
+    pub responders: RefCell<Vec<Responder>>,
+    /// Every message sent via this runtime, including those that failed
+    pub message_log: RefCell<Vec<SentMessage>>,
+    /// Every hash computed via this runtime, with the length of its input
+    pub hash_log: RefCell<Vec<(SupportedHashes, usize)>>,
+}
+
+/// A message sent via [`FakeSyscalls`], along with the result it was given
+#[derive(Clone, Debug)]
+pub struct SentMessage {
+    pub to: Address,
+    pub method: MethodNum,
+    pub params: Option<IpldBlock>,
+    pub value: TokenAmount,
+    pub options: SendOptions,
+    pub result: SendResult,
+}
+
+impl SentMessage {
+    /// Decodes the params of the message, or returns None if it had none
+    pub fn params<P: DeserializeOwned>(&self) -> Option<P> {
+        self.params.as_ref().map(|p| p.deserialize().expect("failed to decode message params"))
+    }
+
+    /// Returns whether the message was delivered and returned successfully
+    pub fn succeeded(&self) -> bool {
+        matches!(&self.result, Ok(response) if response.exit_code.is_success())
+    }
+}
+
+/// An assertion about the messages sent via [`FakeSyscalls`], narrowed down by each filter applied
+/// and checked by one of [`once`](ExpectSend::once), [`times`](ExpectSend::times) or
+/// [`never`](ExpectSend::never)
+#[must_use = "an expectation does nothing unless it is checked"]
+#[derive(Debug)]
+pub struct ExpectSend {
+    matching: Vec<SentMessage>,
+    filters: Vec<String>,
+}
+
+impl ExpectSend {
+    fn filter(mut self, description: String, predicate: impl Fn(&SentMessage) -> bool) -> Self {
+        self.matching.retain(predicate);
+        self.filters.push(description);
+        self
+    }
+
+    /// Only matches messages sent to an address, compared as given to `send`
+    pub fn to(self, address: &Address) -> Self {
+        let address = *address;
+        self.filter(format!("to {address:?}"), |m| m.to == address)
+    }
+
+    /// Only matches messages calling a method
+    pub fn method(self, method: MethodNum) -> Self {
+        self.filter(format!("calling method {method}"), |m| m.method == method)
+    }
+
+    /// Only matches messages sending an amount
+    pub fn value(self, value: &TokenAmount) -> Self {
+        let value = value.clone();
+        self.filter(format!("with value {value:?}"), |m| m.value == value)
+    }
+
+    /// Only matches messages with params that serialize to the same bytes as `params`
+    pub fn params<P: Serialize + fmt::Debug>(self, params: &P) -> Self {
+        let description = format!("with params {params:?}");
+        let expected = IpldBlock::serialize_cbor(params).expect("failed to encode params");
+        self.filter(description, |m| m.params == expected)
+    }
+
+    /// Only matches messages with params that decode to a value satisfying `predicate`
+    pub fn params_matching<P: DeserializeOwned>(self, predicate: impl Fn(&P) -> bool) -> Self {
+        self.filter("with matching params".into(), |m| {
+            m.params.as_ref().and_then(|p| p.deserialize().ok()).is_some_and(|p| predicate(&p))
+        })
+    }
+
+    /// Only matches messages sent with some options
+    pub fn options(self, options: SendOptions) -> Self {
+        self.filter(format!("with options {options:?}"), |m| m.options == options)
+    }
+
+    /// Only matches messages that were delivered and returned successfully
+    pub fn succeeded(self) -> Self {
+        self.filter("succeeding".into(), SentMessage::succeeded)
+    }
+
+    /// Only matches messages that were delivered and returned an exit code
+    pub fn exit_code(self, exit_code: ExitCode) -> Self {
+        self.filter(
+            format!("exiting with {exit_code:?}"),
+            |m| matches!(&m.result, Ok(response) if response.exit_code == exit_code),
+        )
+    }
+
+    /// Only matches messages whose send syscall failed with an error
+    pub fn failed_with(self, error: ErrorNumber) -> Self {
+        self.filter(format!("failing with {error:?}"), |m| matches!(m.result, Err(e) if e == error))
+    }
+
+    /// Asserts that `count` messages matched, returning them
+    pub fn times(self, count: usize) -> Vec<SentMessage> {
+        assert_eq!(
+            self.matching.len(),
+            count,
+            "expected {count} message(s) {}, found {:?}",
+            self.filters.join(", "),
+            self.matching
+        );
+        self.matching
+    }
+
+    /// Asserts that exactly one message matched, returning it
+    pub fn once(self) -> SentMessage {
+        self.times(1).remove(0)
+    }
+
+    /// Asserts that no message matched
+    pub fn never(self) {
+        self.times(0);
+    }
 }
 
 impl FakeSyscalls {
@@ -41,66 +231,250 @@ impl FakeSyscalls {
     pub fn set_caller_id(&self, new_id: ActorID) {
         self.caller_id.replace(new_id);
     }
-}
 
-impl Syscalls for FakeSyscalls {
-    fn root(&self) -> Result<Cid, super::NoStateError> {
-        Ok(*self.root.borrow())
+    /// Set the ActorID returned as origin
+    pub fn set_origin_id(&self, new_id: ActorID) {
+        self.origin_id.replace(new_id);
     }
 
-    fn set_root(&self, cid: &Cid) -> Result<(), super::NoStateError> {
-        self.root.replace(*cid);
-        Ok(())
+    /// Set the method number of the message being handled
+    pub fn set_method_num(&self, method: MethodNum) {
+        self.method_num.replace(method);
     }
 
-    fn receiver(&self) -> fvm_shared::ActorID {
-        self.actor_id
+    /// Set the value received with the message being handled
+    pub fn set_value_received(&self, value: TokenAmount) {
+        self.value_received.replace(value);
     }
 
-    fn caller(&self) -> fvm_shared::ActorID {
-        *self.caller_id.borrow()
+    /// Set the balance of the receiving actor
+    pub fn set_balance(&self, balance: TokenAmount) {
+        self.set_balance_of(self.actor_id, balance);
     }
 
-    fn send(
+    /// Set the balance of an actor
+    pub fn set_balance_of(&self, actor_id: ActorID, balance: TokenAmount) {
+        self.balances.borrow_mut().insert(actor_id, balance);
+    }
+
+    /// Returns the balance of an actor, which is zero unless it was set or sent funds
+    pub fn balance_of(&self, actor_id: ActorID) -> TokenAmount {
+        self.balances.borrow().get(&actor_id).cloned().unwrap_or_default()
+    }
+
+    /// Set the current epoch
+    pub fn set_epoch(&self, epoch: ChainEpoch) {
+        self.epoch.replace(epoch);
+    }
+
+    /// Set the gas remaining for the current invocation
+    pub fn set_gas_available(&self, gas: u64) {
+        self.gas_available.replace(gas);
+    }
+
+    /// Respond to every message sent to an address with a method number with a chosen result
+    ///
+    /// An `Ok` result is returned as the response of the message, whatever its exit code, while an
+    /// `Err` fails the send syscall itself.
+    pub fn respond_to(&self, to: Address, method: MethodNum, result: SendResult) {
+        self.add_responder(Responder::to(to, method, result));
+    }
+
+    /// Respond to the messages for which `respond` returns a result
+    pub fn respond_with<F>(&self, respond: F)
+    where
+        F: Fn(&Address, MethodNum, Option<&IpldBlock>, &TokenAmount) -> Option<SendResult>
+            + 'static,
+    {
+        self.add_responder(Responder::new(respond));
+    }
+
+    /// Add a responder, taking precedence over those added before it
+    pub fn add_responder(&self, responder: Responder) {
+        self.responders.borrow_mut().push(responder);
+    }
+
+    /// Remove all responders, restoring the default of echoing params back
+    pub fn clear_responders(&self) {
+        self.responders.borrow_mut().clear();
+    }
+
+    /// Set the code CID of an actor
+    pub fn set_actor_code(&self, actor_id: ActorID, code: Cid) {
+        self.actor_codes.borrow_mut().insert(actor_id, code);
+    }
+
+    /// Returns every message sent via this runtime, in the order they were sent
+    pub fn sent_messages(&self) -> Vec<SentMessage> {
+        self.message_log.borrow().clone()
+    }
+
+    /// Clears the message log, returning the messages it held
+    pub fn take_sent_messages(&self) -> Vec<SentMessage> {
+        self.message_log.take()
+    }
+
+    /// Returns every hash computed via this runtime, with the length of its input
+    pub fn hashes(&self) -> Vec<(SupportedHashes, usize)> {
+        self.hash_log.borrow().clone()
+    }
+
+    /// Clears the hash log, returning the hashes it held
+    pub fn take_hashes(&self) -> Vec<(SupportedHashes, usize)> {
+        self.hash_log.take()
+    }
+
+    /// Starts an assertion about the messages sent via this runtime
+    ///
+    /// ```
+    /// # use fvm_actor_utils::syscalls::{fake_syscalls::FakeSyscalls, Syscalls};
+    /// # use fvm_shared::{address::Address, econ::TokenAmount};
+    /// let syscalls = FakeSyscalls::default();
+    /// syscalls.send(&Address::new_id(2), 42, None, TokenAmount::default()).unwrap();
+    ///
+    /// syscalls.expect_send().to(&Address::new_id(2)).method(42).succeeded().once();
+    /// syscalls.expect_send().method(43).never();
+    /// ```
+    pub fn expect_send(&self) -> ExpectSend {
+        ExpectSend { matching: self.sent_messages(), filters: Vec::new() }
+    }
+
+    /// Asserts that exactly one receiver hook of a given type was called on an address, returning
+    /// its decoded payload
+    pub fn expect_receiver_hook<P: DeserializeOwned>(
         &self,
-        to: &fvm_shared::address::Address,
-        method: fvm_shared::MethodNum,
-        params: Option<fvm_ipld_encoding::ipld_block::IpldBlock>,
-        value: fvm_shared::econ::TokenAmount,
-    ) -> Result<Response, ErrorNumber> {
+        to: &Address,
+        type_: ReceiverType,
+    ) -> P {
+        let message = self.expect_send().to(to).method(RECEIVER_HOOK_METHOD_NUM).once();
+        let params: UniversalReceiverParams =
+            message.params().expect("receiver hook called without params");
+        assert_eq!(params.type_, type_, "receiver hook called with the wrong receiver type");
+        params.payload.deserialize().expect("failed to decode receiver hook payload")
+    }
+
+    /// Decides the result of a sent message, instantiating the recipient if it is delivered
+    fn deliver(
+        &self,
+        to: &Address,
+        method: MethodNum,
+        params: Option<IpldBlock>,
+        value: TokenAmount,
+        options: SendOptions,
+    ) -> SendResult {
         if *self.abort_next_send.borrow() {
             self.abort_next_send.replace(false);
             Err(ErrorNumber::AssertionFailed)
         } else {
+            // funds can't be transferred by read-only messages
+            if options.is_read_only() && !value.is_zero() {
+                return Err(ErrorNumber::ReadOnly);
+            }
+            if self.balance_of(self.actor_id) < value {
+                return Err(ErrorNumber::InsufficientFunds);
+            }
+
+            // clone the responders so they may themselves add responders
+            let responders = self.responders.borrow().clone();
+            let response = responders
+                .iter()
+                .rev()
+                .find_map(|r| r.respond(to, method, params.as_ref(), &value));
+            // a failed syscall doesn't deliver the message
+            if let Some(Err(err)) = response {
+                return Err(err);
+            }
+
             // sending to an address instantiates it if it isn't already
             let mut map = self.addresses.borrow_mut();
 
-            match to.payload() {
+            let recipient = match to.payload() {
                 // TODO: in a real system, this is fallible if the address does not exist
                 // This impl assumes that any f0 form address is in the map/instantiated but does not check so
                 // Sending to actors should succeed if the actor exists but not instantiate it
-                fvm_shared::address::Payload::ID(_) | fvm_shared::address::Payload::Actor(_) => {
-                    Ok(())
-                }
+                fvm_shared::address::Payload::ID(id) => Some(*id),
+                fvm_shared::address::Payload::Actor(_) => map.get(to).copied(),
                 // Sending to public keys should instantiate the actor
                 fvm_shared::address::Payload::Secp256k1(_)
                 | fvm_shared::address::Payload::BLS(_)
                 | fvm_shared::address::Payload::Delegated(_) => {
-                    if !map.contains_key(to) {
-                        let actor_id = self.next_actor_id.replace_with(|old| *old + 1);
-                        map.insert(*to, actor_id);
-                    }
-                    Ok(())
+                    let actor_id = map
+                        .entry(*to)
+                        .or_insert_with(|| self.next_actor_id.replace_with(|old| *old + 1));
+                    Some(*actor_id)
                 }
-            }?;
+            };
+            drop(map);
+
+            // funds can't be sent to an actor that doesn't exist
+            if recipient.is_none() && !value.is_zero() {
+                return Err(ErrorNumber::NotFound);
+            }
 
             // save the fake message as being sent
-            let message = TestMessage { method, params: params.clone(), value };
+            let message = TestMessage { method, params: params.clone(), value: value.clone() };
             self.last_message.replace(Some(message));
 
-            Ok(Response { exit_code: ExitCode::OK, return_data: params })
+            let response = match response {
+                Some(response) => response,
+                None => Ok(Response { exit_code: ExitCode::OK, return_data: params }),
+            };
+            // value is only transferred if the recipient accepts the message
+            let accepted = matches!(&response, Ok(r) if r.exit_code.is_success());
+            if let Some(recipient) = recipient.filter(|_| accepted && !value.is_zero()) {
+                let mut balances = self.balances.borrow_mut();
+                let from = balances.entry(self.actor_id).or_default();
+                *from = &*from - &value;
+                let to = balances.entry(recipient).or_default();
+                *to = &*to + &value;
+            }
+            response
         }
     }
+}
+
+impl Syscalls for FakeSyscalls {
+    fn root(&self) -> Result<Cid, super::NoStateError> {
+        Ok(*self.root.borrow())
+    }
+
+    fn set_root(&self, cid: &Cid) -> Result<(), super::NoStateError> {
+        self.root.replace(*cid);
+        Ok(())
+    }
+
+    fn receiver(&self) -> fvm_shared::ActorID {
+        self.actor_id
+    }
+
+    fn caller(&self) -> fvm_shared::ActorID {
+        *self.caller_id.borrow()
+    }
+
+    fn send(
+        &self,
+        to: &fvm_shared::address::Address,
+        method: fvm_shared::MethodNum,
+        params: Option<fvm_ipld_encoding::ipld_block::IpldBlock>,
+        value: fvm_shared::econ::TokenAmount,
+    ) -> Result<Response, ErrorNumber> {
+        self.send_with_options(to, method, params, value, SendOptions::default())
+    }
+
+    fn send_with_options(
+        &self,
+        to: &fvm_shared::address::Address,
+        method: fvm_shared::MethodNum,
+        params: Option<fvm_ipld_encoding::ipld_block::IpldBlock>,
+        value: fvm_shared::econ::TokenAmount,
+        options: SendOptions,
+    ) -> Result<Response, ErrorNumber> {
+        let result = self.deliver(to, method, params.clone(), value.clone(), options);
+        let message =
+            SentMessage { to: *to, method, params, value, options, result: result.clone() };
+        self.message_log.borrow_mut().push(message);
+        result
+    }
 
     fn resolve_address(&self, addr: &Address) -> Option<ActorID> {
         // if it is already an ID-address, just return it
@@ -111,4 +485,222 @@ impl Syscalls for FakeSyscalls {
         let map = self.addresses.borrow();
         map.get(addr).copied()
     }
+
+    fn origin(&self) -> ActorID {
+        *self.origin_id.borrow()
+    }
+
+    fn method_number(&self) -> MethodNum {
+        *self.method_num.borrow()
+    }
+
+    fn value_received(&self) -> TokenAmount {
+        self.value_received.borrow().clone()
+    }
+
+    fn current_balance(&self) -> TokenAmount {
+        self.balance_of(self.actor_id)
+    }
+
+    fn curr_epoch(&self) -> ChainEpoch {
+        *self.epoch.borrow()
+    }
+
+    fn gas_available(&self) -> u64 {
+        *self.gas_available.borrow()
+    }
+
+    fn get_actor_code_cid(&self, addr: &Address) -> Option<Cid> {
+        let actor_id = self.resolve_address(addr)?;
+        self.actor_codes.borrow().get(&actor_id).copied()
+    }
+
+    fn hash(&self, hasher: SupportedHashes, data: &[u8]) -> Vec<u8> {
+        self.hash_log.borrow_mut().push((hasher, data.len()));
+        super::hash(hasher, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cid::Cid;
+    use fvm_ipld_encoding::ipld_block::IpldBlock;
+    use fvm_shared::address::{Address, BLS_PUB_LEN};
+    use fvm_shared::crypto::hash::SupportedHashes;
+    use fvm_shared::econ::TokenAmount;
+    use fvm_shared::error::{ErrorNumber, ExitCode};
+    use fvm_shared::Response;
+
+    use super::FakeSyscalls;
+    use crate::messaging::SendOptions;
+    use crate::syscalls::Syscalls;
+
+    #[test]
+    fn reports_settable_environment() {
+        let syscalls = FakeSyscalls::default();
+        syscalls.set_origin_id(100);
+        syscalls.set_method_num(2);
+        syscalls.set_value_received(TokenAmount::from_atto(10));
+        syscalls.set_balance(TokenAmount::from_atto(110));
+        syscalls.set_epoch(1234);
+        syscalls.set_gas_available(1_000_000);
+
+        assert_eq!(syscalls.origin(), 100);
+        assert_eq!(syscalls.method_number(), 2);
+        assert_eq!(syscalls.value_received(), TokenAmount::from_atto(10));
+        assert_eq!(syscalls.current_balance(), TokenAmount::from_atto(110));
+        assert_eq!(syscalls.curr_epoch(), 1234);
+        assert_eq!(syscalls.gas_available(), 1_000_000);
+
+        let code = Cid::default();
+        syscalls.set_actor_code(5, code);
+        assert_eq!(syscalls.get_actor_code_cid(&Address::new_id(5)), Some(code));
+        assert_eq!(syscalls.get_actor_code_cid(&Address::new_id(6)), None);
+    }
+
+    #[test]
+    fn responds_to_messages() {
+        let syscalls = FakeSyscalls::default();
+        let alice = Address::new_id(10);
+        let bob = Address::new_id(11);
+        syscalls.set_balance(TokenAmount::from_atto(1));
+        let params = IpldBlock::serialize_cbor(&"params").unwrap();
+        let rejected = Response {
+            exit_code: ExitCode::USR_FORBIDDEN,
+            return_data: IpldBlock::serialize_cbor(&"rejected").unwrap(),
+        };
+        syscalls.respond_to(alice, 2, Ok(rejected.clone()));
+        syscalls.respond_with(|to, _, _, value| {
+            (*to == Address::new_id(11) && value.is_positive())
+                .then_some(Err(ErrorNumber::NotFound))
+        });
+
+        // unmatched messages succeed, echoing their params
+        let res = syscalls.send(&alice, 3, params.clone(), TokenAmount::default()).unwrap();
+        assert_eq!(res.exit_code, ExitCode::OK);
+        assert_eq!(res.return_data, params);
+        let res = syscalls.send(&alice, 2, params, TokenAmount::default()).unwrap();
+        assert_eq!(res.exit_code, rejected.exit_code);
+        assert_eq!(res.return_data, rejected.return_data);
+        assert_eq!(syscalls.last_message.borrow().as_ref().unwrap().method, 2);
+
+        // a syscall error doesn't deliver the message
+        let err = syscalls.send(&bob, 2, None, TokenAmount::from_atto(1)).unwrap_err();
+        assert_eq!(err, ErrorNumber::NotFound);
+        assert_eq!(syscalls.last_message.borrow().as_ref().unwrap().method, 2);
+
+        // later responders take precedence
+        syscalls.respond_to(alice, 2, Err(ErrorNumber::Forbidden));
+        let err = syscalls.send(&alice, 2, None, TokenAmount::default()).unwrap_err();
+        assert_eq!(err, ErrorNumber::Forbidden);
+        syscalls.clear_responders();
+        assert!(syscalls.send(&bob, 2, None, TokenAmount::from_atto(1)).is_ok());
+    }
+
+    #[test]
+    fn logs_messages() {
+        let syscalls = FakeSyscalls::default();
+        let alice = Address::new_id(10);
+        let params = IpldBlock::serialize_cbor(&(1u64, "one")).unwrap();
+        syscalls.set_balance(TokenAmount::from_atto(1));
+        syscalls.send(&alice, 2, params, TokenAmount::from_atto(1)).unwrap();
+        syscalls.abort_next_send.replace(true);
+        syscalls.send(&alice, 3, None, TokenAmount::default()).unwrap_err();
+        syscalls.respond_to(
+            alice,
+            4,
+            Ok(Response { exit_code: ExitCode::USR_FORBIDDEN, return_data: None }),
+        );
+        syscalls.send(&alice, 4, None, TokenAmount::default()).unwrap();
+
+        let log = syscalls.sent_messages();
+        assert_eq!(log.iter().map(|m| m.method).collect::<Vec<_>>(), [2, 3, 4]);
+        assert_eq!(log[0].params::<(u64, String)>(), Some((1, "one".into())));
+
+        let sent = syscalls.expect_send().to(&alice).succeeded().once();
+        assert_eq!(sent.method, 2);
+        syscalls.expect_send().params(&(1u64, "one")).value(&TokenAmount::from_atto(1)).once();
+        syscalls.expect_send().params_matching(|(n, _): &(u64, String)| *n == 1).once();
+        syscalls.expect_send().method(3).failed_with(ErrorNumber::AssertionFailed).once();
+        syscalls.expect_send().exit_code(ExitCode::USR_FORBIDDEN).once();
+        syscalls.expect_send().to(&alice).times(3);
+
+        // the log can be reset between steps of a test
+        assert_eq!(syscalls.take_sent_messages().len(), 3);
+        syscalls.expect_send().never();
+    }
+
+    #[test]
+    #[should_panic(expected = "expected 1 message(s) calling method 5")]
+    fn reports_unmet_expectations() {
+        let syscalls = FakeSyscalls::default();
+        syscalls.send(&Address::new_id(10), 2, None, TokenAmount::default()).unwrap();
+        syscalls.expect_send().method(5).once();
+    }
+
+    #[test]
+    fn transfers_value() {
+        let syscalls = FakeSyscalls { actor_id: 1, ..Default::default() };
+        let alice = Address::new_id(10);
+        let key = Address::new_bls(&[1; BLS_PUB_LEN]).unwrap();
+        syscalls.set_balance(TokenAmount::from_atto(100));
+
+        syscalls.send(&alice, 0, None, TokenAmount::from_atto(30)).unwrap();
+        assert_eq!(syscalls.current_balance(), TokenAmount::from_atto(70));
+        assert_eq!(syscalls.balance_of(10), TokenAmount::from_atto(30));
+
+        // sending to a new key address instantiates it with the funds
+        syscalls.send(&key, 0, None, TokenAmount::from_atto(20)).unwrap();
+        let key_id = syscalls.resolve_address(&key).unwrap();
+        assert_eq!(syscalls.balance_of(key_id), TokenAmount::from_atto(20));
+
+        let err = syscalls.send(&alice, 0, None, TokenAmount::from_atto(51)).unwrap_err();
+        assert_eq!(err, ErrorNumber::InsufficientFunds);
+        syscalls.expect_send().failed_with(ErrorNumber::InsufficientFunds).once();
+
+        // funds sent with a rejected message stay with the sender
+        let rejected = Response { exit_code: ExitCode::USR_FORBIDDEN, return_data: None };
+        syscalls.respond_to(alice, 2, Ok(rejected));
+        syscalls.send(&alice, 2, None, TokenAmount::from_atto(50)).unwrap();
+        assert_eq!(syscalls.current_balance(), TokenAmount::from_atto(50));
+        assert_eq!(syscalls.balance_of(10), TokenAmount::from_atto(30));
+
+        let unknown = Address::new_actor(b"unknown");
+        let err = syscalls.send(&unknown, 0, None, TokenAmount::from_atto(1)).unwrap_err();
+        assert_eq!(err, ErrorNumber::NotFound);
+    }
+
+    #[test]
+    fn records_send_options() {
+        let syscalls = FakeSyscalls::default();
+        syscalls.set_balance(TokenAmount::from_atto(1));
+        let alice = Address::new_id(10);
+        let query = SendOptions::new().read_only().with_gas_limit(100);
+        syscalls.send_with_options(&alice, 2, None, TokenAmount::default(), query).unwrap();
+        syscalls.send(&alice, 2, None, TokenAmount::default()).unwrap();
+
+        syscalls.expect_send().options(query).once();
+        syscalls.expect_send().options(SendOptions::default()).once();
+        // read-only messages can't transfer funds
+        let err = syscalls.send_with_options(&alice, 2, None, TokenAmount::from_atto(1), query);
+        assert_eq!(err.unwrap_err(), ErrorNumber::ReadOnly);
+    }
+
+    #[test]
+    fn hashes_data() {
+        let syscalls = FakeSyscalls::default();
+        assert_eq!(
+            syscalls.hash(SupportedHashes::Sha2_256, b""),
+            hex_literal("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+        );
+        assert_eq!(syscalls.hash(SupportedHashes::Keccak256, b"").len(), 32);
+        assert_eq!(syscalls.hash(SupportedHashes::Blake2b512, b"").len(), 64);
+        assert_eq!(syscalls.hash(SupportedHashes::Ripemd160, b"").len(), 20);
+        assert_eq!(syscalls.take_hashes().len(), 4);
+        assert!(syscalls.hashes().is_empty());
+    }
+
+    fn hex_literal(hex: &str) -> Vec<u8> {
+        (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect()
+    }
 }
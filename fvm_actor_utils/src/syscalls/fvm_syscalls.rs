@@ -1,11 +1,14 @@
 use anyhow::Result;
+use frc42_dispatch::message::SendOptions;
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_sdk;
-use fvm_shared::sys::SendFlags;
-use fvm_shared::{address::Address, MethodNum, Response};
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::crypto::hash::SupportedHashes;
+use fvm_shared::{address::Address, econ::TokenAmount, MethodNum, Response};
 
 use super::Syscalls;
+use crate::buffered_blockstore::BufferedBlockstore;
 use crate::util::ActorRuntime;
 
 /// Runtime that delegates to fvm_sdk allowing actors to be deployed on-chain
@@ -36,7 +39,19 @@ impl Syscalls for FvmSyscalls {
         params: Option<IpldBlock>,
         value: fvm_shared::econ::TokenAmount,
     ) -> fvm_sdk::SyscallResult<Response> {
-        match fvm_sdk::send::send(to, method, params, value, None, SendFlags::empty()) {
+        self.send_with_options(to, method, params, value, SendOptions::default())
+    }
+
+    fn send_with_options(
+        &self,
+        to: &Address,
+        method: MethodNum,
+        params: Option<IpldBlock>,
+        value: fvm_shared::econ::TokenAmount,
+        options: SendOptions,
+    ) -> fvm_sdk::SyscallResult<Response> {
+        let (gas_limit, flags) = (options.gas_limit(), options.flags());
+        match fvm_sdk::send::send(to, method, params, value, gas_limit, flags) {
             Ok(res) => Ok(Response { exit_code: res.exit_code, return_data: res.return_data }),
             Err(err) => Err(err),
         }
@@ -45,10 +60,52 @@ impl Syscalls for FvmSyscalls {
     fn resolve_address(&self, addr: &Address) -> Option<fvm_shared::ActorID> {
         fvm_sdk::actor::resolve_address(addr)
     }
+
+    fn origin(&self) -> fvm_shared::ActorID {
+        fvm_sdk::message::origin()
+    }
+
+    fn method_number(&self) -> MethodNum {
+        fvm_sdk::message::method_number()
+    }
+
+    fn value_received(&self) -> TokenAmount {
+        fvm_sdk::message::value_received()
+    }
+
+    fn current_balance(&self) -> TokenAmount {
+        fvm_sdk::sself::current_balance()
+    }
+
+    fn curr_epoch(&self) -> ChainEpoch {
+        fvm_sdk::network::curr_epoch()
+    }
+
+    fn gas_available(&self) -> u64 {
+        fvm_sdk::gas::available()
+    }
+
+    fn get_actor_code_cid(&self, addr: &Address) -> Option<cid::Cid> {
+        fvm_sdk::actor::get_actor_code_cid(addr)
+    }
+
+    fn hash(&self, hasher: SupportedHashes, data: &[u8]) -> Vec<u8> {
+        fvm_sdk::crypto::hash_owned(hasher, data)
+    }
 }
 
 impl<S: Syscalls + Clone, BS: Blockstore + Clone> ActorRuntime<S, BS> {
     pub fn new_fvm_runtime() -> ActorRuntime<FvmSyscalls, crate::blockstore::Blockstore> {
         ActorRuntime { syscalls: FvmSyscalls::default(), blockstore: crate::blockstore::Blockstore }
     }
+
+    /// Like [`ActorRuntime::new_fvm_runtime`], but blocks are only written when the new state is
+    /// committed with [`ActorRuntime::set_root`]
+    pub fn new_buffered_fvm_runtime(
+    ) -> ActorRuntime<FvmSyscalls, BufferedBlockstore<crate::blockstore::Blockstore>> {
+        ActorRuntime {
+            syscalls: FvmSyscalls::default(),
+            blockstore: BufferedBlockstore::new(crate::blockstore::Blockstore),
+        }
+    }
 }
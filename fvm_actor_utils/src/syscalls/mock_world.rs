@@ -0,0 +1,580 @@
+//! An in-process world of actors, for testing interactions between actors without building wasm
+//!
+//! A [`MockWorld`] hosts Rust handlers at ID addresses, each with its own state root on a shared
+//! blockstore. Messages sent by a handler are dispatched synchronously into the handler of the
+//! recipient, so nested and re-entrant calls behave as they would on-chain, and the state changes
+//! made by a call are rolled back if it aborts. Read-only messages are enforced, but gas isn't
+//! metered, so gas limits are ignored.
+use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
+
+use cid::Cid;
+use frc42_dispatch::message::SendOptions;
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_shared::address::{Address, Payload};
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::crypto::hash::SupportedHashes;
+use fvm_shared::{
+    econ::TokenAmount, error::ErrorNumber, error::ExitCode, ActorID, MethodNum, Response,
+    METHOD_SEND,
+};
+use num_traits::Zero;
+
+use super::{NoStateError, Syscalls};
+use crate::messaging::RECEIVER_HOOK_METHOD_NUM;
+use crate::shared_blockstore::SharedMemoryBlockstore;
+use crate::util::ActorRuntime;
+
+/// The runtime given to the actors hosted in a [`MockWorld`]
+pub type MockRuntime = ActorRuntime<MockSyscalls, SharedMemoryBlockstore>;
+
+/// The result of invoking a mock actor: its return data, or how it aborted
+pub type InvokeResult = Result<Option<IpldBlock>, ActorAbort>;
+
+/// ID assigned to the first account created implicitly by sending to a new address
+pub const FIRST_ACCOUNT_ID: ActorID = 100;
+
+/// Maximum depth of nested calls, lower than the FVM's limit to stay within the native stack
+pub const MAX_CALL_DEPTH: u32 = 64;
+
+/// Aborts an invocation with an exit code, rolling back the state changes made during it
+#[derive(Clone, Debug)]
+pub struct ActorAbort {
+    pub exit_code: ExitCode,
+    pub return_data: Option<IpldBlock>,
+}
+
+impl ActorAbort {
+    pub fn new(exit_code: ExitCode, return_data: Option<IpldBlock>) -> Self {
+        Self { exit_code, return_data }
+    }
+}
+
+impl From<ExitCode> for ActorAbort {
+    fn from(exit_code: ExitCode) -> Self {
+        Self { exit_code, return_data: None }
+    }
+}
+
+type HandlerFn = dyn Fn(&MockRuntime, MethodNum, Option<IpldBlock>) -> InvokeResult;
+
+#[derive(Clone)]
+struct Handler(Rc<HandlerFn>);
+
+impl fmt::Debug for Handler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Handler")
+    }
+}
+
+#[derive(Clone, Debug)]
+struct MockActor {
+    /// Handles messages sent to the actor, or None for an account
+    handler: Option<Handler>,
+    root: Option<Cid>,
+    code: Cid,
+    balance: TokenAmount,
+}
+
+#[derive(Debug)]
+struct WorldState {
+    actors: HashMap<ActorID, MockActor>,
+    addresses: HashMap<Address, ActorID>,
+    next_actor_id: ActorID,
+    epoch: ChainEpoch,
+    depth: u32,
+}
+
+impl Default for WorldState {
+    fn default() -> Self {
+        Self {
+            actors: HashMap::new(),
+            addresses: HashMap::new(),
+            next_actor_id: FIRST_ACCOUNT_ID,
+            epoch: 0,
+            depth: 0,
+        }
+    }
+}
+
+/// A set of actors hosted in-process, sharing a blockstore
+///
+/// Clones of a world reference the same actors and blockstore.
+#[derive(Clone, Debug, Default)]
+pub struct MockWorld {
+    state: Rc<RefCell<WorldState>>,
+    blockstore: SharedMemoryBlockstore,
+}
+
+impl MockWorld {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hosts an actor at an ID address, with no state until it sets a root
+    ///
+    /// Panics if an actor already exists at the address.
+    pub fn install_actor<F>(&self, actor_id: ActorID, handler: F)
+    where
+        F: Fn(&MockRuntime, MethodNum, Option<IpldBlock>) -> InvokeResult + 'static,
+    {
+        self.insert(actor_id, Some(Handler(Rc::new(handler))));
+    }
+
+    /// Creates an account at an ID address, which accepts plain value transfers and receiver hooks
+    ///
+    /// Panics if an actor already exists at the address.
+    pub fn install_account(&self, actor_id: ActorID) {
+        self.insert(actor_id, None);
+    }
+
+    fn insert(&self, actor_id: ActorID, handler: Option<Handler>) {
+        let actor = MockActor { handler, root: None, code: Cid::default(), balance: Zero::zero() };
+        let previous = self.state.borrow_mut().actors.insert(actor_id, actor);
+        assert!(previous.is_none(), "an actor already exists at ID {actor_id}");
+    }
+
+    /// Returns a runtime acting as an actor outside of any call, e.g. to set up its initial state
+    pub fn runtime(&self, actor_id: ActorID) -> MockRuntime {
+        let frame = Frame {
+            receiver: actor_id,
+            caller: actor_id,
+            origin: actor_id,
+            method: METHOD_SEND,
+            value: Zero::zero(),
+            read_only: false,
+        };
+        ActorRuntime::new(MockSyscalls { world: self.clone(), frame }, self.blockstore.clone())
+    }
+
+    /// Applies a top-level message from an account, dispatching it and any messages it triggers
+    pub fn apply_message(
+        &self,
+        from: ActorID,
+        to: &Address,
+        method: MethodNum,
+        params: Option<IpldBlock>,
+        value: TokenAmount,
+    ) -> Result<Response, ErrorNumber> {
+        self.runtime(from).syscalls.send(to, method, params, value)
+    }
+
+    /// Returns the state root of an actor
+    pub fn root(&self, actor_id: ActorID) -> Option<Cid> {
+        self.state.borrow().actors.get(&actor_id).and_then(|a| a.root)
+    }
+
+    /// Returns the balance of an actor, which is zero for actors that don't exist
+    pub fn balance(&self, actor_id: ActorID) -> TokenAmount {
+        self.state.borrow().actors.get(&actor_id).map_or_else(Zero::zero, |a| a.balance.clone())
+    }
+
+    /// Sets the balance of an existing actor
+    pub fn set_balance(&self, actor_id: ActorID, balance: TokenAmount) {
+        self.actor_mut(actor_id, |a| a.balance = balance);
+    }
+
+    /// Sets the code CID reported for an existing actor
+    pub fn set_actor_code(&self, actor_id: ActorID, code: Cid) {
+        self.actor_mut(actor_id, |a| a.code = code);
+    }
+
+    /// Sets the current epoch
+    pub fn set_epoch(&self, epoch: ChainEpoch) {
+        self.state.borrow_mut().epoch = epoch;
+    }
+
+    /// Returns the blockstore shared by the actors
+    pub fn blockstore(&self) -> &SharedMemoryBlockstore {
+        &self.blockstore
+    }
+
+    fn actor_mut(&self, actor_id: ActorID, f: impl FnOnce(&mut MockActor)) {
+        let mut state = self.state.borrow_mut();
+        let actor = state.actors.get_mut(&actor_id);
+        f(actor.unwrap_or_else(|| panic!("no actor exists at ID {actor_id}")));
+    }
+
+    fn resolve_address(&self, address: &Address) -> Option<ActorID> {
+        match address.payload() {
+            Payload::ID(id) => Some(*id),
+            _ => self.state.borrow().addresses.get(address).copied(),
+        }
+    }
+
+    /// Resolves the recipient of a message, creating an account for a new public key address
+    fn resolve_recipient(&self, to: &Address, read_only: bool) -> Result<ActorID, ErrorNumber> {
+        if let Some(actor_id) = self.resolve_address(to) {
+            let exists = self.state.borrow().actors.contains_key(&actor_id);
+            return if exists { Ok(actor_id) } else { Err(ErrorNumber::NotFound) };
+        }
+        match to.payload() {
+            Payload::Secp256k1(_) | Payload::BLS(_) | Payload::Delegated(_) if read_only => {
+                Err(ErrorNumber::ReadOnly)
+            }
+            Payload::Secp256k1(_) | Payload::BLS(_) | Payload::Delegated(_) => {
+                let mut state = self.state.borrow_mut();
+                let actor_id = state.next_actor_id;
+                state.next_actor_id += 1;
+                state.addresses.insert(*to, actor_id);
+                drop(state);
+                self.install_account(actor_id);
+                Ok(actor_id)
+            }
+            _ => Err(ErrorNumber::NotFound),
+        }
+    }
+
+    /// Delivers a message from the receiver of a frame, rolling back its effects if it aborts
+    fn send(
+        &self,
+        sender: &Frame,
+        to: &Address,
+        method: MethodNum,
+        params: Option<IpldBlock>,
+        value: TokenAmount,
+        options: SendOptions,
+    ) -> Result<Response, ErrorNumber> {
+        if self.state.borrow().depth >= MAX_CALL_DEPTH {
+            return Err(ErrorNumber::LimitExceeded);
+        }
+        // read-only calls can't transfer funds and make every call nested in them read-only
+        let read_only = sender.read_only || options.is_read_only();
+        if read_only && !value.is_zero() {
+            return Err(ErrorNumber::ReadOnly);
+        }
+        let snapshot = self.snapshot();
+        let receiver = self.resolve_recipient(to, read_only)?;
+
+        // transfer the value before invoking the recipient, so it can spend it
+        if !value.is_zero() {
+            if self.balance(sender.receiver) < value {
+                self.restore(snapshot);
+                return Err(ErrorNumber::InsufficientFunds);
+            }
+            let mut state = self.state.borrow_mut();
+            let from = state.actors.get_mut(&sender.receiver).expect("sender has a balance");
+            from.balance = &from.balance - &value;
+            let to = state.actors.get_mut(&receiver).expect("recipient was resolved");
+            to.balance = &to.balance + &value;
+        }
+
+        let handler = self.state.borrow().actors[&receiver].handler.clone();
+        let result = match handler {
+            Some(Handler(handler)) => {
+                let frame = Frame {
+                    receiver,
+                    caller: sender.receiver,
+                    origin: sender.origin,
+                    method,
+                    value,
+                    read_only,
+                };
+                let runtime = ActorRuntime::new(
+                    MockSyscalls { world: self.clone(), frame },
+                    self.blockstore.clone(),
+                );
+                self.state.borrow_mut().depth += 1;
+                let result = handler(&runtime, method, params);
+                self.state.borrow_mut().depth -= 1;
+                result
+            }
+            // accounts accept value and receiver hooks
+            None => match method {
+                METHOD_SEND | RECEIVER_HOOK_METHOD_NUM => Ok(None),
+                _ => Err(ExitCode::USR_UNHANDLED_MESSAGE.into()),
+            },
+        };
+
+        match result {
+            Ok(return_data) => Ok(Response { exit_code: ExitCode::OK, return_data }),
+            Err(abort) => {
+                self.restore(snapshot);
+                Ok(Response { exit_code: abort.exit_code, return_data: abort.return_data })
+            }
+        }
+    }
+
+    fn snapshot(&self) -> (HashMap<ActorID, MockActor>, HashMap<Address, ActorID>) {
+        let state = self.state.borrow();
+        (state.actors.clone(), state.addresses.clone())
+    }
+
+    fn restore(
+        &self,
+        (actors, addresses): (HashMap<ActorID, MockActor>, HashMap<Address, ActorID>),
+    ) {
+        let mut state = self.state.borrow_mut();
+        state.actors = actors;
+        state.addresses = addresses;
+    }
+}
+
+/// The message being handled by an actor in a [`MockWorld`]
+#[derive(Clone, Debug)]
+struct Frame {
+    receiver: ActorID,
+    caller: ActorID,
+    origin: ActorID,
+    method: MethodNum,
+    value: TokenAmount,
+    read_only: bool,
+}
+
+/// Syscalls for an actor handling a message in a [`MockWorld`]
+#[derive(Clone, Debug)]
+pub struct MockSyscalls {
+    world: MockWorld,
+    frame: Frame,
+}
+
+impl MockSyscalls {
+    /// Returns the world the actor is hosted in
+    pub fn world(&self) -> &MockWorld {
+        &self.world
+    }
+}
+
+impl Syscalls for MockSyscalls {
+    fn root(&self) -> Result<Cid, NoStateError> {
+        self.world.root(self.frame.receiver).ok_or(NoStateError)
+    }
+
+    fn set_root(&self, cid: &Cid) -> Result<(), NoStateError> {
+        if self.frame.read_only {
+            return Err(NoStateError);
+        }
+        let mut state = self.world.state.borrow_mut();
+        let actor = state.actors.get_mut(&self.frame.receiver).ok_or(NoStateError)?;
+        actor.root = Some(*cid);
+        Ok(())
+    }
+
+    fn receiver(&self) -> ActorID {
+        self.frame.receiver
+    }
+
+    fn caller(&self) -> ActorID {
+        self.frame.caller
+    }
+
+    fn send(
+        &self,
+        to: &Address,
+        method: MethodNum,
+        params: Option<IpldBlock>,
+        value: TokenAmount,
+    ) -> Result<Response, ErrorNumber> {
+        self.send_with_options(to, method, params, value, SendOptions::default())
+    }
+
+    fn send_with_options(
+        &self,
+        to: &Address,
+        method: MethodNum,
+        params: Option<IpldBlock>,
+        value: TokenAmount,
+        options: SendOptions,
+    ) -> Result<Response, ErrorNumber> {
+        self.world.send(&self.frame, to, method, params, value, options)
+    }
+
+    fn resolve_address(&self, addr: &Address) -> Option<ActorID> {
+        self.world.resolve_address(addr)
+    }
+
+    fn origin(&self) -> ActorID {
+        self.frame.origin
+    }
+
+    fn method_number(&self) -> MethodNum {
+        self.frame.method
+    }
+
+    fn value_received(&self) -> TokenAmount {
+        self.frame.value.clone()
+    }
+
+    fn current_balance(&self) -> TokenAmount {
+        self.world.balance(self.frame.receiver)
+    }
+
+    fn curr_epoch(&self) -> ChainEpoch {
+        self.world.state.borrow().epoch
+    }
+
+    /// The world doesn't meter gas, so this is always the maximum
+    fn gas_available(&self) -> u64 {
+        u64::MAX
+    }
+
+    fn get_actor_code_cid(&self, addr: &Address) -> Option<Cid> {
+        let actor_id = self.world.resolve_address(addr)?;
+        self.world.state.borrow().actors.get(&actor_id).map(|a| a.code)
+    }
+
+    fn hash(&self, hasher: SupportedHashes, data: &[u8]) -> Vec<u8> {
+        super::hash(hasher, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fvm_ipld_encoding::ipld_block::IpldBlock;
+    use fvm_ipld_encoding::CborStore;
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+    use fvm_shared::error::{ErrorNumber, ExitCode};
+    use fvm_shared::{ActorID, MethodNum};
+    use multihash::Code;
+
+    use num_traits::Zero;
+
+    use super::{ActorAbort, InvokeResult, MockRuntime, MockWorld, FIRST_ACCOUNT_ID};
+    use crate::messaging::SendOptions;
+    use crate::syscalls::Syscalls;
+
+    const COUNTER: ActorID = 10;
+    const RELAY: ActorID = 11;
+    const ALICE: ActorID = 12;
+
+    const INCREMENT: MethodNum = 2;
+    const INCREMENT_THEN_FAIL: MethodNum = 3;
+
+    fn load_count(rt: &MockRuntime) -> u64 {
+        rt.root_cid().ok().map_or(0, |root| rt.blockstore.get_cbor(&root).unwrap().unwrap())
+    }
+
+    fn save_count(rt: &MockRuntime, count: u64) -> Result<(), ActorAbort> {
+        let root = rt.blockstore.put_cbor(&count, Code::Blake2b256).unwrap();
+        rt.set_root(&root).map_err(|_| ExitCode::USR_ILLEGAL_STATE.into())
+    }
+
+    /// Counts increments, optionally aborting after saving the new count
+    fn counter(rt: &MockRuntime, method: MethodNum, _: Option<IpldBlock>) -> InvokeResult {
+        let count = load_count(rt) + 1;
+        save_count(rt, count)?;
+        match method {
+            INCREMENT => Ok(IpldBlock::serialize_cbor(&count).unwrap()),
+            INCREMENT_THEN_FAIL => Err(ExitCode::USR_FORBIDDEN.into()),
+            _ => Err(ExitCode::USR_UNHANDLED_MESSAGE.into()),
+        }
+    }
+
+    /// Forwards a message to the counter, then calls back into itself once
+    fn relay(rt: &MockRuntime, method: MethodNum, _: Option<IpldBlock>) -> InvokeResult {
+        let res = rt.syscalls.send(&Address::new_id(COUNTER), method, None, Default::default());
+        if rt.caller() != RELAY {
+            rt.syscalls
+                .send(&Address::new_id(RELAY), INCREMENT, None, Default::default())
+                .map_err(|_| ExitCode::USR_ASSERTION_FAILED)?;
+        }
+        let res = res.map_err(|_| ExitCode::USR_ASSERTION_FAILED)?;
+        match res.exit_code {
+            ExitCode::OK => Ok(res.return_data),
+            code => Err(code.into()),
+        }
+    }
+
+    fn world() -> MockWorld {
+        let world = MockWorld::new();
+        world.install_actor(COUNTER, counter);
+        world.install_actor(RELAY, relay);
+        world.install_account(ALICE);
+        world
+    }
+
+    #[test]
+    fn dispatches_nested_calls() {
+        let world = world();
+        let res = world.apply_message(
+            ALICE,
+            &Address::new_id(RELAY),
+            INCREMENT,
+            None,
+            Default::default(),
+        );
+        let res = res.unwrap();
+        assert_eq!(res.exit_code, ExitCode::OK);
+        // the relay called the counter, then re-entered itself to call it again
+        assert_eq!(res.return_data.unwrap().deserialize::<u64>().unwrap(), 1);
+        assert_eq!(load_count(&world.runtime(COUNTER)), 2);
+    }
+
+    #[test]
+    fn rolls_back_aborted_calls() {
+        let world = world();
+        world
+            .apply_message(ALICE, &Address::new_id(COUNTER), INCREMENT, None, Default::default())
+            .unwrap();
+        let root = world.root(COUNTER);
+
+        let res = world
+            .apply_message(
+                ALICE,
+                &Address::new_id(RELAY),
+                INCREMENT_THEN_FAIL,
+                None,
+                Default::default(),
+            )
+            .unwrap();
+        assert_eq!(res.exit_code, ExitCode::USR_FORBIDDEN);
+        assert_eq!(world.root(COUNTER), root);
+        assert_eq!(load_count(&world.runtime(COUNTER)), 1);
+
+        let res =
+            world.apply_message(ALICE, &Address::new_id(99), INCREMENT, None, Default::default());
+        assert_eq!(res.unwrap_err(), ErrorNumber::NotFound);
+    }
+
+    #[test]
+    fn enforces_read_only_calls() {
+        let world = world();
+        let rt = world.runtime(ALICE);
+        let read_only = SendOptions::new().read_only();
+        let counter = Address::new_id(COUNTER);
+
+        // the counter can't save its state, nor can the relay when calling it
+        let res = rt.syscalls.send_with_options(&counter, INCREMENT, None, Zero::zero(), read_only);
+        assert_eq!(res.unwrap().exit_code, ExitCode::USR_ILLEGAL_STATE);
+        assert_eq!(world.root(COUNTER), None);
+        let relay = Address::new_id(RELAY);
+        let res = rt.syscalls.send_with_options(&relay, INCREMENT, None, Zero::zero(), read_only);
+        assert_eq!(res.unwrap().exit_code, ExitCode::USR_ILLEGAL_STATE);
+        assert_eq!(world.root(COUNTER), None);
+
+        world.set_balance(ALICE, TokenAmount::from_atto(1));
+        let res =
+            rt.syscalls.send_with_options(&counter, 0, None, TokenAmount::from_atto(1), read_only);
+        assert_eq!(res.unwrap_err(), ErrorNumber::ReadOnly);
+    }
+
+    #[test]
+    fn transfers_value() {
+        let world = world();
+        world.set_balance(ALICE, TokenAmount::from_atto(100));
+        let key = Address::new_bls(&[1; fvm_shared::address::BLS_PUB_LEN]).unwrap();
+
+        // sending to a new key address creates an account
+        world.apply_message(ALICE, &key, 0, None, TokenAmount::from_atto(60)).unwrap();
+        assert_eq!(world.runtime(ALICE).resolve_id(&key).unwrap(), FIRST_ACCOUNT_ID);
+        assert_eq!(world.balance(FIRST_ACCOUNT_ID), TokenAmount::from_atto(60));
+        assert_eq!(world.balance(ALICE), TokenAmount::from_atto(40));
+
+        let err =
+            world.apply_message(ALICE, &key, 0, None, TokenAmount::from_atto(60)).unwrap_err();
+        assert_eq!(err, ErrorNumber::InsufficientFunds);
+
+        // value sent with an aborted call is returned
+        world
+            .apply_message(
+                ALICE,
+                &Address::new_id(COUNTER),
+                INCREMENT_THEN_FAIL,
+                None,
+                TokenAmount::from_atto(40),
+            )
+            .unwrap();
+        assert_eq!(world.balance(ALICE), TokenAmount::from_atto(40));
+        assert_eq!(world.balance(COUNTER), TokenAmount::default());
+    }
+}
@@ -1,12 +1,17 @@
 use cid::Cid;
+use frc42_dispatch::message::SendOptions;
 use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::crypto::hash::SupportedHashes;
 use fvm_shared::{
     address::Address, econ::TokenAmount, error::ErrorNumber, ActorID, MethodNum, Response,
 };
+use multihash::{Code, MultihashDigest};
 use thiserror::Error;
 
 pub mod fake_syscalls;
 pub mod fvm_syscalls;
+pub mod mock_world;
 
 /// Copied to avoid linking against `fvm_sdk` for non-WASM targets
 #[derive(Copy, Clone, Debug, Error)]
@@ -44,9 +49,59 @@ pub trait Syscalls {
         value: TokenAmount,
     ) -> Result<Response, ErrorNumber>;
 
+    /// Sends a message to an actor with a gas limit, read-only or other flags
+    ///
+    /// The default implementation sends messages with default options through `send` and fails
+    /// with `IllegalArgument` for any other options, so implementations that can honour them
+    /// should override it.
+    fn send_with_options(
+        &self,
+        to: &Address,
+        method: MethodNum,
+        params: Option<IpldBlock>,
+        value: TokenAmount,
+        options: SendOptions,
+    ) -> Result<Response, ErrorNumber> {
+        if options != SendOptions::default() {
+            return Err(ErrorNumber::IllegalArgument);
+        }
+        self.send(to, method, params, value)
+    }
+
     /// Resolves the ID address of an actor.
     ///
     /// Returns None if the address cannot be resolved. Successfully resolving an address doesn't
     /// necessarily mean the actor exists (e.g., if the addresss was already an actor ID).
     fn resolve_address(&self, addr: &Address) -> Option<ActorID>;
+
+    /// Returns the ID address of the actor that signed the top-level message
+    fn origin(&self) -> ActorID;
+
+    /// Returns the method number of the message being handled
+    fn method_number(&self) -> MethodNum;
+
+    /// Returns the value sent with the message being handled
+    fn value_received(&self) -> TokenAmount;
+
+    /// Returns the current balance of the actor, including the value received
+    fn current_balance(&self) -> TokenAmount;
+
+    /// Returns the current epoch
+    fn curr_epoch(&self) -> ChainEpoch;
+
+    /// Returns the gas remaining for the current invocation
+    fn gas_available(&self) -> u64;
+
+    /// Returns the code CID of an actor, or None if it doesn't exist
+    fn get_actor_code_cid(&self, addr: &Address) -> Option<Cid>;
+
+    /// Hashes data with one of the hash functions supported by the FVM, returning the digest
+    fn hash(&self, hasher: SupportedHashes, data: &[u8]) -> Vec<u8>;
+}
+
+/// Hashes data natively, for the syscalls implementations used in tests
+pub(crate) fn hash(hasher: SupportedHashes, data: &[u8]) -> Vec<u8> {
+    // the supported hashes are identified by their multicodec codes
+    let code = Code::try_from(hasher as u64).expect("supported hash is a multihash code");
+    code.digest(data).digest().to_vec()
 }
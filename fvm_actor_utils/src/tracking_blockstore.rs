@@ -0,0 +1,129 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+use anyhow::Result;
+use cid::Cid;
+use fvm_ipld_blockstore::Blockstore;
+
+use crate::blockstore::CommitBlockstore;
+
+/// IPLD traffic recorded by a [`TrackingBlockstore`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BlockstoreStats {
+    /// Number of blocks requested, including ones that weren't found
+    pub gets: u64,
+    /// Number of blocks written
+    pub puts: u64,
+    /// Total size of the blocks returned by gets
+    pub bytes_read: u64,
+    /// Total size of the blocks written
+    pub bytes_written: u64,
+    /// Number of distinct CIDs read or written
+    pub distinct_cids: u64,
+}
+
+/// A blockstore wrapper that counts the reads and writes passing through it
+///
+/// Useful to measure the IPLD cost of an operation in unit tests, e.g. by calling
+/// [`TrackingBlockstore::reset`] before it and checking [`TrackingBlockstore::stats`] after.
+#[derive(Debug, Default)]
+pub struct TrackingBlockstore<BS: Blockstore> {
+    store: BS,
+    stats: RefCell<BlockstoreStats>,
+    touched: RefCell<HashSet<Cid>>,
+}
+
+impl<BS: Blockstore> TrackingBlockstore<BS> {
+    pub fn new(store: BS) -> Self {
+        Self { store, stats: Default::default(), touched: Default::default() }
+    }
+
+    /// The wrapped blockstore, whose traffic isn't tracked
+    pub fn inner(&self) -> &BS {
+        &self.store
+    }
+
+    /// Snapshot of the traffic since creation or the last reset
+    pub fn stats(&self) -> BlockstoreStats {
+        *self.stats.borrow()
+    }
+
+    /// Clears the recorded traffic
+    pub fn reset(&self) {
+        self.stats.replace(BlockstoreStats::default());
+        self.touched.borrow_mut().clear();
+    }
+
+    /// Returns the recorded traffic and resets it
+    pub fn take_stats(&self) -> BlockstoreStats {
+        let stats = self.stats();
+        self.reset();
+        stats
+    }
+
+    fn touch(&self, k: &Cid) {
+        if self.touched.borrow_mut().insert(*k) {
+            self.stats.borrow_mut().distinct_cids += 1;
+        }
+    }
+}
+
+impl<BS: CommitBlockstore> CommitBlockstore for TrackingBlockstore<BS> {
+    fn commit(&self, root: &Cid) -> Result<()> {
+        self.store.commit(root)
+    }
+}
+
+impl<BS: Blockstore> Blockstore for TrackingBlockstore<BS> {
+    fn get(&self, k: &Cid) -> Result<Option<Vec<u8>>> {
+        let block = self.store.get(k)?;
+        self.touch(k);
+        let mut stats = self.stats.borrow_mut();
+        stats.gets += 1;
+        stats.bytes_read += block.as_ref().map_or(0, |b| b.len() as u64);
+        Ok(block)
+    }
+
+    fn put_keyed(&self, k: &Cid, block: &[u8]) -> Result<()> {
+        self.store.put_keyed(k, block)?;
+        self.touch(k);
+        let mut stats = self.stats.borrow_mut();
+        stats.puts += 1;
+        stats.bytes_written += block.len() as u64;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cid::multihash::Code;
+    use fvm_ipld_blockstore::{Blockstore, MemoryBlockstore};
+    use fvm_ipld_encoding::{to_vec, CborStore};
+
+    use super::{BlockstoreStats, TrackingBlockstore};
+
+    #[test]
+    fn counts_traffic() {
+        let bs = TrackingBlockstore::new(MemoryBlockstore::new());
+        let size = to_vec(&"block").unwrap().len() as u64;
+        let cid = bs.put_cbor(&"block", Code::Blake2b256).unwrap();
+        let other = bs.put_cbor(&"other", Code::Blake2b256).unwrap();
+        bs.get(&cid).unwrap();
+        bs.get(&cid).unwrap();
+        // checking for a block reads it, like the FVM does
+        assert!(bs.has(&other).unwrap());
+        bs.inner().get(&other).unwrap();
+
+        let stats = bs.stats();
+        assert_eq!(stats.gets, 3);
+        assert_eq!(stats.puts, 2);
+        assert_eq!(stats.bytes_read, 3 * size);
+        assert_eq!(stats.bytes_written, 2 * size);
+        assert_eq!(stats.distinct_cids, 2);
+
+        assert_eq!(bs.take_stats(), stats);
+        assert_eq!(bs.stats(), BlockstoreStats::default());
+        bs.get(&cid).unwrap();
+        assert_eq!(bs.stats().distinct_cids, 1);
+    }
+}
@@ -8,16 +8,20 @@ use fvm_shared::{MethodNum, Response};
 use num_traits::Zero;
 use thiserror::Error;
 
-use crate::messaging::{Messaging, MessagingError, Result as MessagingResult};
+use crate::blockstore::CommitBlockstore;
+use crate::messaging::{Messaging, MessagingError, Result as MessagingResult, SendOptions};
 use crate::shared_blockstore::SharedMemoryBlockstore;
 use crate::syscalls::fake_syscalls::FakeSyscalls;
 use crate::syscalls::NoStateError;
 use crate::syscalls::Syscalls;
+use crate::tracking_blockstore::TrackingBlockstore;
 
-#[derive(Error, Clone, Debug)]
+#[derive(Error, Debug)]
 pub enum ActorError {
     #[error("root state not found {0}")]
     NoState(#[from] NoStateError),
+    #[error("failed to flush state {0}")]
+    Flush(#[source] anyhow::Error),
 }
 
 type ActorResult<T> = std::result::Result<T, ActorError>;
@@ -26,6 +30,7 @@ impl From<&ActorError> for ExitCode {
     fn from(error: &ActorError) -> Self {
         match error {
             ActorError::NoState(_) => ExitCode::USR_NOT_FOUND,
+            ActorError::Flush(_) => ExitCode::USR_ILLEGAL_STATE,
         }
     }
 }
@@ -59,6 +64,15 @@ impl<S: Syscalls, BS: Blockstore> ActorRuntime<S, BS> {
         }
     }
 
+    /// Creates a test runtime whose memory blockstore records IPLD traffic
+    pub fn new_tracking_test_runtime(
+    ) -> ActorRuntime<FakeSyscalls, TrackingBlockstore<MemoryBlockstore>> {
+        ActorRuntime {
+            syscalls: FakeSyscalls::default(),
+            blockstore: TrackingBlockstore::new(MemoryBlockstore::default()),
+        }
+    }
+
     /// Returns the address of the current actor as an ActorID
     pub fn actor_id(&self) -> ActorID {
         self.syscalls.receiver()
@@ -79,6 +93,18 @@ impl<S: Syscalls, BS: Blockstore> ActorRuntime<S, BS> {
         Ok(self.syscalls.send(to, method, params, value)?)
     }
 
+    /// Sends a message to an actor with a gas limit, read-only or other flags
+    pub fn send_with_options(
+        &self,
+        to: &Address,
+        method: MethodNum,
+        params: Option<IpldBlock>,
+        value: TokenAmount,
+        options: SendOptions,
+    ) -> MessagingResult<Response> {
+        Ok(self.syscalls.send_with_options(to, method, params, value, options)?)
+    }
+
     /// Attempts to resolve the given address to its ID address form
     ///
     /// Returns MessagingError::AddressNotResolved if the address could not be resolved
@@ -116,11 +142,6 @@ impl<S: Syscalls, BS: Blockstore> ActorRuntime<S, BS> {
         Ok(self.syscalls.root().map_err(|_err| NoStateError)?)
     }
 
-    /// Set the root cid of the actor's state
-    pub fn set_root(&self, cid: &Cid) -> ActorResult<()> {
-        Ok(self.syscalls.set_root(cid).map_err(|_err| NoStateError)?)
-    }
-
     /// Attempts to compare two addresses, seeing if they would resolve to the same Actor without
     /// actually instantiating accounts for them
     ///
@@ -150,6 +171,19 @@ impl<S: Syscalls, BS: Blockstore> ActorRuntime<S, BS> {
     }
 }
 
+impl<S: Syscalls, BS: CommitBlockstore> ActorRuntime<S, BS> {
+    /// Set the root cid of the actor's state
+    ///
+    /// The blockstore is committed first, so a [`BufferedBlockstore`] writes the blocks
+    /// reachable from the new root before it is set.
+    ///
+    /// [`BufferedBlockstore`]: crate::buffered_blockstore::BufferedBlockstore
+    pub fn set_root(&self, cid: &Cid) -> ActorResult<()> {
+        self.blockstore.commit(cid).map_err(ActorError::Flush)?;
+        Ok(self.syscalls.set_root(cid).map_err(|_err| NoStateError)?)
+    }
+}
+
 /// Convenience impl encapsulating the blockstore functionality
 impl<S: Syscalls, BS: Blockstore> Blockstore for ActorRuntime<S, BS> {
     fn get(&self, k: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
@@ -172,4 +206,16 @@ impl<S: Syscalls, BS: Blockstore> Messaging for ActorRuntime<S, BS> {
         let res = self.syscalls.send(to, method, params, value);
         Ok(res?)
     }
+
+    fn send_with_options(
+        &self,
+        to: &Address,
+        method: fvm_shared::MethodNum,
+        params: Option<IpldBlock>,
+        value: fvm_shared::econ::TokenAmount,
+        options: SendOptions,
+    ) -> crate::messaging::Result<Response> {
+        let res = self.syscalls.send_with_options(to, method, params, value, options);
+        Ok(res?)
+    }
 }
@@ -0,0 +1,110 @@
+use fvm_actor_utils::client;
+use fvm_actor_utils::messaging::{Messaging, MessagingError, Result, SendOptions};
+use fvm_actor_utils::syscalls::fake_syscalls::FakeSyscalls;
+use fvm_actor_utils::util::ActorRuntime;
+use fvm_ipld_blockstore::MemoryBlockstore;
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::{ErrorNumber, ExitCode};
+use fvm_shared::{MethodNum, Response};
+
+client! {
+    /// A client for an actor with a few methods of different shapes
+    pub struct EchoClient {
+        /// FakeSyscalls returns the params of a message as its return data
+        #[method("Echo")]
+        fn echo(message: String) -> String;
+        #[method("Notify")]
+        fn notify(count: u64);
+        #[method("Ping")]
+        fn ping() -> String;
+    }
+}
+
+fn runtime() -> ActorRuntime<FakeSyscalls, MemoryBlockstore> {
+    ActorRuntime::<FakeSyscalls, MemoryBlockstore>::new_test_runtime()
+}
+
+#[test]
+fn sends_typed_messages() {
+    let runtime = runtime();
+    let client = EchoClient::new(&runtime, Address::new_id(100));
+    assert_eq!(client.address(), &Address::new_id(100));
+
+    assert_eq!(client.echo("hello".into()).unwrap(), "hello");
+    let message = runtime.syscalls.last_message.borrow().clone().unwrap();
+    assert_eq!(message.method, frc42_dispatch::method_hash!("Echo"));
+    assert_eq!(message.value, TokenAmount::default());
+
+    client.notify(3).unwrap();
+    let message = runtime.syscalls.last_message.borrow().clone().unwrap();
+    assert_eq!(message.method, frc42_dispatch::method_hash!("Notify"));
+    assert_eq!(message.params.unwrap().deserialize::<u64>().unwrap(), 3);
+}
+
+#[test]
+fn reports_missing_return() {
+    let runtime = runtime();
+    let client = EchoClient::new(&runtime, Address::new_id(100));
+
+    // a method without params gets no return data back from FakeSyscalls
+    assert!(matches!(
+        client.ping(),
+        Err(MessagingError::MissingReturn { method, .. }) if method == frc42_dispatch::method_hash!("Ping")
+    ));
+}
+
+#[test]
+fn reports_syscall_errors() {
+    let runtime = runtime();
+    let client = EchoClient::new(&runtime, Address::new_id(100));
+
+    runtime.syscalls.abort_next_send.replace(true);
+    assert!(matches!(client.notify(1), Err(MessagingError::Syscall(_))));
+}
+
+/// Rejects every message with the same exit code
+struct Aborting(ExitCode);
+
+impl Messaging for Aborting {
+    fn send(
+        &self,
+        _to: &Address,
+        _method: MethodNum,
+        _params: Option<IpldBlock>,
+        _value: TokenAmount,
+    ) -> Result<Response> {
+        Ok(Response { exit_code: self.0, return_data: None })
+    }
+}
+
+#[test]
+fn checks_exit_codes() {
+    let messaging = Aborting(ExitCode::USR_FORBIDDEN);
+    let client = EchoClient::new(&messaging, Address::new_id(100));
+
+    let err = client.echo("hello".into()).unwrap_err();
+    assert!(matches!(err, MessagingError::Aborted { exit_code: ExitCode::USR_FORBIDDEN, .. }));
+    assert_eq!(ExitCode::from(&err), ExitCode::USR_FORBIDDEN);
+
+    // system exit codes can't be passed on by the calling actor
+    let messaging = Aborting(ExitCode::SYS_OUT_OF_GAS);
+    let client = EchoClient::new(&messaging, Address::new_id(100));
+    let err = client.notify(1).unwrap_err();
+    assert_eq!(ExitCode::from(&err), ExitCode::USR_UNSPECIFIED);
+}
+
+#[test]
+fn rejects_unsupported_send_options() {
+    let messaging = Aborting(ExitCode::OK);
+    let to = Address::new_id(100);
+
+    // implementations that only provide `send` can't honour other options
+    let read_only = SendOptions::new().read_only();
+    let err = messaging.send_with_options(&to, 2, None, TokenAmount::default(), read_only);
+    assert!(matches!(err, Err(MessagingError::Syscall(ErrorNumber::IllegalArgument))));
+
+    let res = messaging.send_with_options(&to, 2, None, TokenAmount::default(), SendOptions::new());
+    assert_eq!(res.unwrap().exit_code, ExitCode::OK);
+}
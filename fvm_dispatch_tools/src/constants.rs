@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+use clap::ValueEnum;
+
+/// The ways a list of method numbers can be printed
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// One decimal method number per line
+    Plain,
+    /// A JSON array of names and numbers
+    Json,
+    /// A Rust module of `u64` constants
+    Rust,
+    /// A TypeScript module of exported constants
+    Typescript,
+    /// A Go package of `uint64` constants
+    Go,
+    /// A Solidity library of `uint64` constants
+    Solidity,
+}
+
+const HEADER: &str = "Generated by fvm_dispatch_tools, FRC-0042 method numbers";
+
+/// Renders method names and their numbers in the given format
+///
+/// Fails if the output would declare the same constant twice, either because a name is repeated
+/// or because distinct names map to the same identifier.
+pub fn render(format: OutputFormat, methods: &[(String, u64)]) -> Result<String> {
+    check_identifiers(format, methods)?;
+    let mut out = String::new();
+    match format {
+        OutputFormat::Plain => {
+            for (_, number) in methods.iter() {
+                out.push_str(&format!("{number}\n"));
+            }
+        }
+        OutputFormat::Json => {
+            let entries = methods
+                .iter()
+                .map(|(name, number)| serde_json::json!({ "name": name, "number": number }))
+                .collect::<Vec<_>>();
+            // serializing a Vec of json values can't fail
+            out.push_str(&serde_json::to_string_pretty(&entries).unwrap());
+            out.push('\n');
+        }
+        OutputFormat::Rust => {
+            out.push_str(&format!("//! {HEADER}\n"));
+            for (name, number) in methods.iter() {
+                out.push_str(&format!(
+                    "\n/// `{name}`\npub const {}: u64 = {number:#010x};\n",
+                    screaming_snake_case(name)
+                ));
+            }
+        }
+        OutputFormat::Typescript => {
+            out.push_str(&format!("// {HEADER}\n"));
+            for (name, number) in methods.iter() {
+                out.push_str(&format!(
+                    "\n/** `{name}` */\nexport const {} = {number:#010x};\n",
+                    screaming_snake_case(name)
+                ));
+            }
+        }
+        OutputFormat::Go => {
+            out.push_str(&format!(
+                "// Code generated by fvm_dispatch_tools. DO NOT EDIT.\n// {HEADER}\n\npackage methods\n\nconst (\n"
+            ));
+            for (name, number) in methods.iter() {
+                out.push_str(&format!("\t{} uint64 = {number:#010x}\n", go_identifier(name)));
+            }
+            out.push_str(")\n");
+        }
+        OutputFormat::Solidity => {
+            out.push_str(&format!(
+                "// SPDX-License-Identifier: MIT OR Apache-2.0\n// {HEADER}\npragma solidity >=0.8.0;\n\nlibrary MethodNumbers {{\n"
+            ));
+            for (name, number) in methods.iter() {
+                out.push_str(&format!(
+                    "    uint64 internal constant {} = {number:#010x};\n",
+                    screaming_snake_case(name)
+                ));
+            }
+            out.push_str("}\n");
+        }
+    }
+    Ok(out)
+}
+
+/// The constant name a method is declared under, for formats that declare constants
+fn identifier(format: OutputFormat, name: &str) -> Option<String> {
+    match format {
+        OutputFormat::Plain | OutputFormat::Json => None,
+        OutputFormat::Go => Some(go_identifier(name)),
+        OutputFormat::Rust | OutputFormat::Typescript | OutputFormat::Solidity => {
+            Some(screaming_snake_case(name))
+        }
+    }
+}
+
+fn check_identifiers(format: OutputFormat, methods: &[(String, u64)]) -> Result<()> {
+    let mut declared: HashMap<String, &str> = HashMap::new();
+    for (name, _) in methods.iter() {
+        let ident = match identifier(format, name) {
+            Some(ident) => ident,
+            None => continue,
+        };
+        match declared.get(ident.as_str()) {
+            Some(other) if other == name => bail!("duplicate method name `{name}`"),
+            Some(other) => {
+                bail!("method names `{other}` and `{name}` both map to the constant `{ident}`")
+            }
+            None => {
+                declared.insert(ident, name);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Exported Go identifiers can't start with `_`, so constants are prefixed with `Method`
+fn go_identifier(name: &str) -> String {
+    format!("Method{}", name.trim_start_matches('_'))
+}
+
+/// Converts an FRC-0042 method name (e.g. `TransferFrom` or `InvokeEVM`) to a constant name
+/// (`TRANSFER_FROM`, `INVOKE_EVM`)
+pub fn screaming_snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::with_capacity(name.len() + 4);
+    for (i, c) in chars.iter().enumerate() {
+        if i > 0 && c.is_ascii_uppercase() {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase());
+            if prev.is_ascii_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_ascii_uppercase() && next_is_lower)
+            {
+                out.push('_');
+            }
+        }
+        out.push(c.to_ascii_uppercase());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render, screaming_snake_case, OutputFormat};
+
+    #[test]
+    fn converts_names() {
+        assert_eq!(screaming_snake_case("Constructor"), "CONSTRUCTOR");
+        assert_eq!(screaming_snake_case("TransferFrom"), "TRANSFER_FROM");
+        assert_eq!(screaming_snake_case("InvokeEVM"), "INVOKE_EVM");
+        assert_eq!(screaming_snake_case("EVMInvoke"), "EVM_INVOKE");
+        assert_eq!(screaming_snake_case("Create2"), "CREATE2");
+        assert_eq!(screaming_snake_case("_Method"), "_METHOD");
+    }
+
+    #[test]
+    fn renders_formats() {
+        let methods = vec![("Constructor".to_string(), 1), ("Name".to_string(), 0x02ea015c)];
+
+        let render = |format| render(format, &methods).unwrap();
+
+        assert_eq!(render(OutputFormat::Plain), "1\n48890204\n");
+        assert!(render(OutputFormat::Rust).contains("pub const NAME: u64 = 0x02ea015c;"));
+        assert!(render(OutputFormat::Typescript).contains("export const NAME = 0x02ea015c;"));
+        assert!(render(OutputFormat::Go)
+            .starts_with("// Code generated by fvm_dispatch_tools. DO NOT EDIT.\n"));
+        assert!(render(OutputFormat::Go).contains("\tMethodName uint64 = 0x02ea015c\n"));
+        assert!(
+            render(OutputFormat::Solidity).contains("uint64 internal constant NAME = 0x02ea015c;")
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&render(OutputFormat::Json)).unwrap();
+        assert_eq!(json[1]["name"], "Name");
+        assert_eq!(json[1]["number"], 48890204);
+    }
+
+    #[test]
+    fn rejects_clashing_constants() {
+        let methods = |names: &[&str]| names.iter().map(|n| (n.to_string(), 1)).collect::<Vec<_>>();
+
+        let duplicate = methods(&["Name", "Name"]);
+        let err = render(OutputFormat::Rust, &duplicate).unwrap_err();
+        assert_eq!(err.to_string(), "duplicate method name `Name`");
+        // lists of numbers don't declare anything
+        assert!(render(OutputFormat::Plain, &duplicate).is_ok());
+
+        let err =
+            render(OutputFormat::Solidity, &methods(&["InvokeEVM", "InvokeEvm"])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "method names `InvokeEVM` and `InvokeEvm` both map to the constant `INVOKE_EVM`"
+        );
+        let err = render(OutputFormat::Go, &methods(&["Name", "_Name"])).unwrap_err();
+        assert!(err.to_string().contains("`MethodName`"));
+        assert!(render(OutputFormat::Rust, &methods(&["Name", "_Name"])).is_ok());
+    }
+}
@@ -0,0 +1,41 @@
+use anyhow::{anyhow, Result};
+use clap::Args;
+use frc42_dispatch::convention::Convention;
+use frc42_dispatch::hash::{Blake2bHasher, MethodResolver};
+use frc42_syntax::parse_convention;
+use syn::Attribute;
+
+/// Options selecting the hashing convention used to resolve method names
+#[derive(Args, Debug)]
+pub struct ConventionArgs {
+    /// Version of the FRC-0042 hashing convention
+    #[clap(long = "convention", default_value_t = 1)]
+    version: u32,
+    /// Hash names with a custom domain separator prefix instead of the version's prefix, e.g. for
+    /// a private namespace
+    #[clap(long)]
+    prefix: Option<String>,
+}
+
+impl ConventionArgs {
+    pub fn convention(&self) -> Result<Convention> {
+        let convention = Convention::version(self.version)
+            .ok_or_else(|| anyhow!("unknown FRC-0042 convention version {}", self.version))?;
+        Ok(match &self.prefix {
+            Some(prefix) => convention.with_prefix(prefix.clone()),
+            None => convention,
+        })
+    }
+
+    pub fn resolver(&self) -> Result<MethodResolver<Blake2bHasher>> {
+        Ok(MethodResolver::with_convention(Blake2bHasher {}, self.convention()?))
+    }
+}
+
+/// Reads the hashing convention selected by an `#[actor(...)]` attribute
+pub fn actor_convention(attr: &Attribute) -> syn::Result<Convention> {
+    if attr.tokens.is_empty() {
+        return Ok(Convention::default());
+    }
+    Ok(attr.parse_args_with(parse_convention)?.unwrap_or_default())
+}
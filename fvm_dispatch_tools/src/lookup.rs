@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Args;
+use frc42_dispatch::hash::{Blake2bHasher, MethodResolver};
+
+use crate::convention::ConventionArgs;
+
+/// Method names from the FRC standards and builtin actors
+const DICTIONARY: &str = include_str!("dictionary.txt");
+
+#[derive(Args, Debug)]
+pub struct LookupArgs {
+    /// Method numbers to look up, in decimal or 0x-prefixed hex
+    #[clap(required = true)]
+    numbers: Vec<String>,
+    /// Extra files of candidate method names, one per line
+    #[clap(long = "wordlist", short)]
+    wordlists: Vec<PathBuf>,
+    #[clap(flatten)]
+    convention: ConventionArgs,
+}
+
+pub fn run(args: LookupArgs) -> Result<()> {
+    let mut dictionary = Dictionary::new(args.convention.resolver()?);
+    dictionary.add_wordlist(DICTIONARY, "bundled");
+    for path in args.wordlists.iter() {
+        let words = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        dictionary.add_wordlist(&words, &path.display().to_string());
+    }
+
+    let mut unknown = 0;
+    for input in args.numbers.iter() {
+        let number = parse_method_number(input)?;
+        let matches = dictionary.lookup(number);
+        match matches.len() {
+            0 => {
+                println!("{number:#010x} ({number}): no known method name");
+                unknown += 1;
+            }
+            1 => println!("{number:#010x} ({number}): {}", matches[0]),
+            _ => {
+                println!("{number:#010x} ({number}): collision between {} names", matches.len());
+                for candidate in matches.iter() {
+                    println!("    {candidate}");
+                }
+            }
+        }
+    }
+
+    if unknown > 0 {
+        bail!("{unknown} method number(s) could not be resolved");
+    }
+    Ok(())
+}
+
+/// Parses a method number given in decimal or 0x-prefixed hex
+pub fn parse_method_number(input: &str) -> Result<u64> {
+    let input = input.trim();
+    let parsed = match input.strip_prefix("0x").or_else(|| input.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => input.parse(),
+    };
+    parsed.map_err(|e| anyhow!("invalid method number `{input}`: {e}"))
+}
+
+/// A method name and the standards or wordlists it was found in
+#[derive(Debug, PartialEq, Eq)]
+pub struct Candidate {
+    pub name: String,
+    pub sources: Vec<String>,
+}
+
+impl std::fmt::Display for Candidate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} [{}]", self.name, self.sources.join(", "))
+    }
+}
+
+/// Candidate method names indexed by method number
+#[derive(Default)]
+struct Dictionary {
+    resolver: MethodResolver<Blake2bHasher>,
+    names: BTreeMap<u64, BTreeMap<String, Vec<String>>>,
+}
+
+impl Dictionary {
+    fn new(resolver: MethodResolver<Blake2bHasher>) -> Self {
+        Self { resolver, names: BTreeMap::new() }
+    }
+
+    /// Adds the names in a wordlist
+    ///
+    /// A `[section]` line names the source of the names that follow it, otherwise names are
+    /// attributed to `default_source`. Names that don't follow FRC-0042 are skipped with a warning.
+    fn add_wordlist(&mut self, words: &str, default_source: &str) {
+        let mut source = default_source.to_string();
+        for line in words.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                source = section.trim().to_string();
+                continue;
+            }
+            match self.resolver.method_number(line) {
+                Ok(number) => {
+                    let sources =
+                        self.names.entry(number).or_default().entry(line.to_string()).or_default();
+                    if !sources.contains(&source) {
+                        sources.push(source.clone());
+                    }
+                }
+                Err(e) => eprintln!("skipping `{line}` from {default_source}: {e}"),
+            }
+        }
+    }
+
+    /// All known names that resolve to a method number
+    fn lookup(&self, number: u64) -> Vec<Candidate> {
+        self.names
+            .get(&number)
+            .map(|names| {
+                names
+                    .iter()
+                    .map(|(name, sources)| Candidate {
+                        name: name.clone(),
+                        sources: sources.clone(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use frc42_dispatch::convention::Convention;
+    use frc42_dispatch::hash::{Blake2bHasher, MethodResolver};
+
+    use super::{parse_method_number, Dictionary, DICTIONARY};
+
+    #[test]
+    fn parses_numbers() {
+        assert_eq!(parse_method_number("0x02ea015c").unwrap(), 0x02ea015c);
+        assert_eq!(parse_method_number("48890204").unwrap(), 48890204);
+        assert!(parse_method_number("0xzz").is_err());
+        assert!(parse_method_number("Name").is_err());
+    }
+
+    #[test]
+    fn finds_bundled_names() {
+        let mut dictionary = Dictionary::default();
+        dictionary.add_wordlist(DICTIONARY, "bundled");
+
+        let matches = dictionary.lookup(0x02ea015c);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].name, "Name");
+        assert!(matches[0].sources.contains(&"FRC-0046".to_string()));
+        assert!(matches[0].sources.contains(&"FRC-0053".to_string()));
+
+        assert_eq!(dictionary.lookup(1)[0].name, "Constructor");
+        assert!(dictionary.lookup(12345).is_empty());
+    }
+
+    #[test]
+    fn reports_collisions() {
+        let mut dictionary = Dictionary::default();
+        dictionary.add_wordlist("# comment\n[mine]\nMethod46776\nMethod85157\nnotValid\n", "test");
+
+        let matches = dictionary.lookup(0x0977a84c);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].name, "Method46776");
+        assert_eq!(matches[1].name, "Method85157");
+        assert_eq!(matches[1].sources, vec!["mine".to_string()]);
+    }
+
+    #[test]
+    fn resolves_with_a_convention() {
+        let private = Convention::V1.with_prefix("acme|");
+        let resolver = MethodResolver::with_convention(Blake2bHasher {}, private);
+        let mut dictionary = Dictionary::new(resolver.clone());
+        dictionary.add_wordlist("Name\n", "test");
+
+        assert!(dictionary.lookup(0x02ea015c).is_empty());
+        assert_eq!(dictionary.lookup(resolver.method_number("Name").unwrap())[0].name, "Name");
+    }
+}
@@ -1,15 +1,25 @@
-mod blake2b;
+mod constants;
+mod convention;
+mod lookup;
+mod manifest;
+mod scan;
+mod sources;
 
 use std::io::{self, BufRead};
 use std::process::exit;
 
-use blake2b::Blake2bHasher;
-use clap::Parser;
-use frc42_dispatch::hash::MethodResolver;
+use anyhow::{bail, Context, Result};
+use clap::{Parser, Subcommand};
+use constants::OutputFormat;
+use convention::ConventionArgs;
+use lookup::LookupArgs;
+use manifest::ManifestCommand;
+use scan::ScanArgs;
 
 const LONG_ABOUT: &str =
-    "Pass a single method name as a command line argument or a list of method names, separated by \
-new-lines to stdin. The output is a list of hashes, one per method name.";
+    "Pass method names as command line arguments or a list of method names, separated by \
+new-lines to stdin. The output is a list of hashes, one per method name, or a module of constants \
+in the language chosen with --format. Exits with an error if any name is invalid.";
 
 /// Takes a method name and converts it to an FRC-0042 compliant method number
 ///
@@ -19,44 +29,82 @@ new-lines to stdin. The output is a list of hashes, one per method name.";
 #[clap(
     version,
     about,
-    long_about = Some(LONG_ABOUT)
+    long_about = Some(LONG_ABOUT),
+    args_conflicts_with_subcommands = true
 )]
 struct Args {
-    /// Method name to hash
-    method_name: Option<String>,
+    /// Method names to hash
+    method_names: Vec<String>,
+
+    /// How to print the method numbers
+    #[clap(long, value_enum, default_value = "plain")]
+    format: OutputFormat,
+
+    #[clap(flatten)]
+    convention: ConventionArgs,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Emit or validate the interface manifest of an actor crate
+    #[clap(subcommand)]
+    Manifest(ManifestCommand),
+    /// Find the known method names that resolve to a method number
+    Lookup(LookupArgs),
+    /// Audit the method names used in a source tree
+    Scan(ScanArgs),
 }
 
 fn main() {
     let args = Args::parse();
-    let resolver = MethodResolver::new(Blake2bHasher {});
-    let method_name = args.method_name;
+    let result = match args.command {
+        Some(Command::Manifest(command)) => manifest::run(command),
+        Some(Command::Lookup(args)) => lookup::run(args),
+        Some(Command::Scan(args)) => scan::run(args),
+        None => hash_names(args.method_names, args.format, &args.convention),
+    };
+    if let Err(e) = result {
+        eprintln!("Error: {e:#}");
+        exit(1);
+    }
+}
 
-    if method_name.is_none() {
+/// Prints the method numbers of names passed as arguments, or read from stdin if there are none
+fn hash_names(names: Vec<String>, format: OutputFormat, convention: &ConventionArgs) -> Result<()> {
+    let names = if names.is_empty() {
         // read from std-in if no name passed in
-        let stdin = io::stdin();
-        let mut handle = stdin.lock();
-        let mut line = String::new();
-
-        loop {
-            let num_read = handle.read_line(&mut line).unwrap();
-            if num_read == 0 {
-                break;
+        let mut names = Vec::new();
+        for line in io::stdin().lock().lines() {
+            let line = line.context("failed to read method names from stdin")?;
+            let name = line.trim();
+            if !name.is_empty() {
+                names.push(name.to_string());
             }
-            let method_name = line.trim().to_string();
-            let method_number = resolver.method_number(&method_name).unwrap();
-            println!("{method_number}");
-            line.clear();
         }
+        names
+    } else {
+        names
+    };
 
-        exit(0);
-    }
-
-    match resolver.method_number(method_name.unwrap().as_str()) {
-        Ok(method_number) => {
-            println!("{method_number}");
-        }
-        Err(e) => {
-            println!("Error computing method name: {e:?}")
+    let resolver = convention.resolver()?;
+    let mut methods = Vec::with_capacity(names.len());
+    let mut invalid = 0;
+    for name in names.into_iter() {
+        match resolver.method_number(&name) {
+            Ok(number) => methods.push((name, number)),
+            Err(e) => {
+                eprintln!("invalid method name `{name}`: {e}");
+                invalid += 1;
+            }
         }
     }
+    if invalid > 0 {
+        bail!("{invalid} method name(s) could not be hashed");
+    }
+
+    print!("{}", constants::render(format, &methods)?);
+    Ok(())
 }
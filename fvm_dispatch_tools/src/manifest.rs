@@ -0,0 +1,375 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use clap::{Subcommand, ValueEnum};
+use frc42_dispatch::convention::Convention;
+use frc42_dispatch::hash::{Blake2bHasher, MethodResolver};
+use frc42_dispatch::manifest::{normalize_type_name, ActorManifest, MethodManifest};
+use frc42_syntax::{is_unit, returns, MethodAttr, Returns};
+use quote::ToTokens;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::visit::Visit;
+use syn::{braced, parenthesized, FnArg, ImplItem, ItemImpl, LitStr, Token, Type};
+
+use crate::convention::{actor_convention, ConventionArgs};
+use crate::sources::{is_test_module, parse_file, rust_sources};
+
+#[derive(Subcommand, Debug)]
+pub enum ManifestCommand {
+    /// Print the manifest of every `#[actor]` impl and `actor_manifest!` declaration in a crate
+    Emit {
+        /// Path to the actor crate
+        crate_path: PathBuf,
+        #[clap(long, value_enum, default_value = "json")]
+        format: Format,
+        /// Write the manifest to a file instead of stdout
+        #[clap(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Check that a manifest file matches the methods declared in a crate
+    Validate {
+        /// Path to the actor crate
+        crate_path: PathBuf,
+        /// Manifest file to check
+        manifest: PathBuf,
+        #[clap(long, value_enum, default_value = "json")]
+        format: Format,
+        /// The convention used to check the published method numbers
+        #[clap(flatten)]
+        convention: ConventionArgs,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Format {
+    Json,
+    Cbor,
+}
+
+pub fn run(command: ManifestCommand) -> Result<()> {
+    match command {
+        ManifestCommand::Emit { crate_path, format, output } => {
+            let manifests = scan_crate(&crate_path)?;
+            let bytes = match format {
+                Format::Json => {
+                    let mut json = serde_json::to_vec_pretty(&manifests)?;
+                    json.push(b'\n');
+                    json
+                }
+                Format::Cbor => fvm_ipld_encoding::to_vec(&manifests)?,
+            };
+            match output {
+                Some(path) => fs::write(&path, bytes)
+                    .with_context(|| format!("failed to write {}", path.display()))?,
+                None => {
+                    use std::io::Write;
+                    std::io::stdout().write_all(&bytes)?;
+                }
+            }
+            Ok(())
+        }
+        ManifestCommand::Validate { crate_path, manifest, format, convention } => {
+            let bytes = fs::read(&manifest)
+                .with_context(|| format!("failed to read {}", manifest.display()))?;
+            let published: Vec<ActorManifest> = match format {
+                Format::Json => serde_json::from_slice(&bytes)?,
+                Format::Cbor => fvm_ipld_encoding::from_slice(&bytes)?,
+            };
+            let resolver = convention.resolver()?;
+            for actor in published.iter() {
+                actor.validate(&resolver).with_context(|| format!("in actor {}", actor.name))?;
+            }
+
+            let declared = scan_crate(&crate_path)?;
+            let problems = compare(&declared, &published);
+            if problems.is_empty() {
+                println!("{} matches {}", manifest.display(), crate_path.display());
+                Ok(())
+            } else {
+                for problem in problems.iter() {
+                    eprintln!("{problem}");
+                }
+                bail!("{} does not match {}", manifest.display(), crate_path.display())
+            }
+        }
+    }
+}
+
+/// Builds manifests for every actor declared in the sources of a crate
+pub fn scan_crate(crate_path: &Path) -> Result<Vec<ActorManifest>> {
+    let mut visitor = ManifestVisitor::default();
+    for file in rust_sources(&crate_path.join("src"))?.iter() {
+        visitor.visit_file(&parse_file(file)?);
+    }
+
+    if let Some(err) = visitor.errors.first() {
+        bail!("{err}");
+    }
+    Ok(visitor.manifests)
+}
+
+/// Lists the differences between the manifests declared in a crate and a published manifest
+fn compare(declared: &[ActorManifest], published: &[ActorManifest]) -> Vec<String> {
+    let mut problems = Vec::new();
+    for actor in declared.iter() {
+        match published.iter().find(|a| a.name == actor.name) {
+            None => problems.push(format!("actor {} is missing from the manifest", actor.name)),
+            Some(other) if other != actor => {
+                let mut differences = Vec::new();
+                for method in actor.methods.iter() {
+                    match other.methods.iter().find(|m| m.name == method.name) {
+                        None => differences
+                            .push(format!("{}: method {} is missing", actor.name, method.name)),
+                        Some(m) if m != method => differences.push(format!(
+                            "{}: method {} differs, expected {:?} but found {:?}",
+                            actor.name, method.name, method, m
+                        )),
+                        Some(_) => {}
+                    }
+                }
+                for method in other.methods.iter() {
+                    if !actor.methods.iter().any(|m| m.name == method.name) {
+                        differences.push(format!(
+                            "{}: method {} is not exported by the crate",
+                            actor.name, method.name
+                        ));
+                    }
+                }
+                if differences.is_empty() {
+                    differences
+                        .push(format!("{}: methods are listed in a different order", actor.name));
+                }
+                problems.append(&mut differences);
+            }
+            Some(_) => {}
+        }
+    }
+    for actor in published.iter() {
+        if !declared.iter().any(|a| a.name == actor.name) {
+            problems.push(format!("actor {} is not declared in the crate", actor.name));
+        }
+    }
+    problems
+}
+
+#[derive(Default)]
+struct ManifestVisitor {
+    manifests: Vec<ActorManifest>,
+    errors: Vec<String>,
+}
+
+impl ManifestVisitor {
+    fn method(
+        &mut self,
+        name: &LitStr,
+        params: Option<&Type>,
+        returns: Option<&Type>,
+        convention: &Convention,
+    ) -> MethodManifest {
+        let resolver = MethodResolver::with_convention(Blake2bHasher {}, convention.clone());
+        let number = match resolver.method_number(&name.value()) {
+            Ok(number) => number,
+            Err(e) => {
+                self.errors.push(format!("invalid method name \"{}\": {e}", name.value()));
+                0
+            }
+        };
+        MethodManifest {
+            name: name.value(),
+            number,
+            params: params.map(type_name),
+            returns: returns.map(type_name),
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for ManifestVisitor {
+    fn visit_item_mod(&mut self, item: &'ast syn::ItemMod) {
+        // test modules aren't part of the deployed actor
+        if !is_test_module(item) {
+            syn::visit::visit_item_mod(self, item);
+        }
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast ItemImpl) {
+        if let Some(attr) = item.attrs.iter().find(|attr| is_path(&attr.path, "actor")) {
+            let convention = match actor_convention(attr) {
+                Ok(convention) => convention,
+                Err(e) => {
+                    self.errors.push(format!("failed to parse #[actor] attribute: {e}"));
+                    Convention::default()
+                }
+            };
+            let mut manifest = ActorManifest::new(&type_name(&item.self_ty));
+            for impl_item in item.items.iter() {
+                let method = match impl_item {
+                    ImplItem::Method(method) => method,
+                    _ => continue,
+                };
+                let attr = method
+                    .attrs
+                    .iter()
+                    .filter(|attr| attr.path.is_ident("method"))
+                    .find_map(|attr| attr.parse_args::<MethodAttr>().ok());
+                if let Some(MethodAttr { name, result }) = attr {
+                    let params = method.sig.inputs.iter().find_map(|input| match input {
+                        FnArg::Typed(pat_type) => Some(&*pat_type.ty),
+                        FnArg::Receiver(_) => None,
+                    });
+                    let returns = match returns(&method.sig.output, result) {
+                        Returns::Unit => None,
+                        Returns::Value(ty) => Some(ty),
+                        Returns::Result(ok) => ok.filter(|ok| !is_unit(ok)),
+                    };
+                    let method = self.method(&name, params, returns, &convention);
+                    manifest.methods.push(method);
+                }
+            }
+            self.manifests.push(manifest);
+        }
+        syn::visit::visit_item_impl(self, item);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        if is_path(&mac.path, "actor_manifest") {
+            match mac.parse_body::<ManifestDeclaration>() {
+                Ok(declaration) => {
+                    let mut manifest = ActorManifest::new(&declaration.actor.value());
+                    for method in declaration.methods.iter() {
+                        let method = self.method(
+                            &method.name,
+                            method.params.as_ref(),
+                            method.returns.as_ref(),
+                            &Convention::default(),
+                        );
+                        manifest.methods.push(method);
+                    }
+                    self.manifests.push(manifest);
+                }
+                Err(e) => self.errors.push(format!("failed to parse actor_manifest!: {e}")),
+            }
+        }
+        syn::visit::visit_macro(self, mac);
+    }
+}
+
+/// The body of an `actor_manifest!` invocation
+pub struct ManifestDeclaration {
+    pub actor: LitStr,
+    pub methods: Punctuated<MethodDeclaration, Token![,]>,
+}
+
+pub struct MethodDeclaration {
+    pub name: LitStr,
+    pub params: Option<Type>,
+    pub returns: Option<Type>,
+}
+
+impl Parse for ManifestDeclaration {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let actor = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let content;
+        braced!(content in input);
+        let methods = content.parse_terminated(MethodDeclaration::parse)?;
+        Ok(Self { actor, methods })
+    }
+}
+
+impl Parse for MethodDeclaration {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        let content;
+        parenthesized!(content in input);
+        let params = if content.is_empty() { None } else { Some(content.parse()?) };
+        let returns = if input.peek(Token![->]) {
+            input.parse::<Token![->]>()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(Self { name, params, returns })
+    }
+}
+
+fn is_path(path: &syn::Path, name: &str) -> bool {
+    path.segments.last().is_some_and(|segment| segment.ident == name)
+}
+
+fn type_name(ty: &Type) -> String {
+    normalize_type_name(&ty.to_token_stream().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use frc42_dispatch::method_hash;
+    use syn::visit::Visit;
+
+    use super::ManifestVisitor;
+
+    #[test]
+    fn reads_return_types() {
+        let mut visitor = ManifestVisitor::default();
+        visitor.visit_file(
+            &syn::parse_file(
+                r#"
+#[actor]
+impl Actor {
+    #[method("Balance")]
+    fn balance() -> Result<TokenAmount, ActorError> {}
+
+    #[method("Query")]
+    fn query() -> QueryResult {}
+
+    #[method("Burn", result)]
+    fn burn(params: BurnParams) -> ActorResult<()> {}
+
+    #[method("Mint", result)]
+    fn mint() -> ActorResult<MintReturn> {}
+}
+"#,
+            )
+            .unwrap(),
+        );
+
+        let returns = visitor.manifests[0]
+            .methods
+            .iter()
+            .map(|m| (m.name.as_str(), m.returns.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            returns,
+            vec![
+                ("Balance", Some("TokenAmount")),
+                ("Query", Some("QueryResult")),
+                ("Burn", None),
+                ("Mint", Some("MintReturn")),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_actor_conventions() {
+        let mut visitor = ManifestVisitor::default();
+        visitor.visit_file(
+            &syn::parse_file(
+                r#"
+#[actor(prefix = "acme|")]
+impl Actor {
+    #[method("Transfer")]
+    fn transfer() {}
+}
+"#,
+            )
+            .unwrap(),
+        );
+
+        assert!(visitor.errors.is_empty());
+        assert_eq!(
+            visitor.manifests[0].methods[0].number,
+            method_hash!("Transfer", prefix = "acme|")
+        );
+    }
+}
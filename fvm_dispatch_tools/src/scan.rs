@@ -0,0 +1,487 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use clap::Args;
+use frc42_dispatch::convention::Convention;
+use frc42_dispatch::exports::EXPORTS_METHOD_NAME;
+use frc42_dispatch::hash::{Blake2bHasher, MethodResolver};
+use frc42_syntax::{parse_convention, MethodAttr};
+use proc_macro2::{Delimiter, Spacing, TokenStream, TokenTree};
+use syn::parse::{Parse, ParseStream, Parser};
+use syn::visit::Visit;
+use syn::{Attribute, ItemImpl, ItemMod, LitStr, Token};
+
+use crate::convention::actor_convention;
+use crate::manifest::ManifestDeclaration;
+use crate::sources::{is_test_module, parse_file, rust_sources};
+
+#[derive(Args, Debug)]
+pub struct ScanArgs {
+    /// Source directory or file to scan
+    path: PathBuf,
+    /// Also scan `#[cfg(test)]` modules
+    #[clap(long)]
+    include_tests: bool,
+}
+
+/// A method name found in the source
+#[derive(Debug)]
+pub struct Occurrence {
+    pub name: String,
+    /// The convention the name is hashed with
+    pub convention: Convention,
+    pub number: std::result::Result<u64, String>,
+    pub file: PathBuf,
+    pub line: usize,
+}
+
+impl Occurrence {
+    fn location(&self) -> String {
+        format!("{}:{}", self.file.display(), self.line)
+    }
+}
+
+pub fn run(args: ScanArgs) -> Result<()> {
+    let occurrences = scan(&args.path, args.include_tests)?;
+    print_table(&occurrences);
+
+    let problems = problems(&occurrences);
+    if !problems.is_empty() {
+        eprintln!();
+        for problem in problems.iter() {
+            eprintln!("{problem}");
+        }
+        bail!("found {} problem(s) in {}", problems.len(), args.path.display());
+    }
+    Ok(())
+}
+
+/// Finds every method name used with `method_hash!`, `match_method!`, `actor_manifest!` or
+/// `#[method]` under a path
+pub fn scan(path: &Path, include_tests: bool) -> Result<Vec<Occurrence>> {
+    let mut occurrences = Vec::new();
+    for file in rust_sources(path)?.iter() {
+        let syntax = parse_file(file)?;
+        let mut visitor = ScanVisitor::new(file, include_tests, &mut occurrences);
+        visitor.visit_file(&syntax);
+    }
+    Ok(occurrences)
+}
+
+/// Reports invalid names and distinct names that resolve to the same method number under the
+/// same convention
+pub fn problems(occurrences: &[Occurrence]) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut by_number: BTreeMap<(u64, &str), BTreeMap<&str, Vec<String>>> = BTreeMap::new();
+    for occurrence in occurrences.iter() {
+        match &occurrence.number {
+            Ok(number) => by_number
+                .entry((*number, occurrence.convention.prefix()))
+                .or_default()
+                .entry(&occurrence.name)
+                .or_default()
+                .push(occurrence.location()),
+            Err(e) => problems.push(format!(
+                "invalid method name \"{}\" at {}: {e}",
+                occurrence.name,
+                occurrence.location()
+            )),
+        }
+    }
+    for ((number, prefix), names) in by_number.iter().filter(|(_, names)| names.len() > 1) {
+        let names = names
+            .iter()
+            .map(|(name, locations)| format!("\"{name}\" ({})", locations.join(", ")))
+            .collect::<Vec<_>>();
+        let convention = match *prefix == Convention::V1.prefix() {
+            true => String::new(),
+            false => format!(" with prefix \"{prefix}\""),
+        };
+        problems
+            .push(format!("collision on method number {number}{convention}: {}", names.join(", ")));
+    }
+    problems
+}
+
+fn print_table(occurrences: &[Occurrence]) {
+    let mut sorted = occurrences.iter().collect::<Vec<_>>();
+    sorted.sort_by(|a, b| (&a.name, &a.file, a.line).cmp(&(&b.name, &b.file, b.line)));
+    let rows = sorted
+        .iter()
+        .map(|o| {
+            let number = match &o.number {
+                Ok(number) => number.to_string(),
+                Err(_) => "invalid".to_string(),
+            };
+            (o.name.as_str(), number, o.location())
+        })
+        .collect::<Vec<_>>();
+
+    let name_width = rows.iter().map(|r| r.0.len()).chain([4]).max().unwrap_or_default();
+    let number_width = rows.iter().map(|r| r.1.len()).chain([6]).max().unwrap_or_default();
+    println!("{:name_width$}  {:number_width$}  LOCATION", "NAME", "NUMBER");
+    for (name, number, location) in rows.iter() {
+        println!("{name:name_width$}  {number:number_width$}  {location}");
+    }
+}
+
+struct ScanVisitor<'a> {
+    file: &'a Path,
+    include_tests: bool,
+    occurrences: &'a mut Vec<Occurrence>,
+    /// The convention of the `#[actor]` impl being visited, used for its `#[method]` attributes
+    actor_convention: Result<Convention, String>,
+}
+
+/// The arguments of `method_hash!`: a name and an optional hashing convention
+struct MethodHashArgs {
+    name: LitStr,
+    convention: Result<Convention, String>,
+}
+
+impl Parse for MethodHashArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        let convention = match input.parse::<Option<Token![,]>>()? {
+            Some(_) => read_convention(input),
+            None => Ok(Convention::V1),
+        };
+        Ok(Self { name, convention })
+    }
+}
+
+/// Reads convention arguments, keeping an unknown version as an error to report against the names
+/// hashed with it
+fn read_convention(input: ParseStream) -> Result<Convention, String> {
+    parse_convention(input).map(Option::unwrap_or_default).map_err(|e| {
+        // skip whatever is left so the names can still be recorded
+        let _ = input.parse::<TokenStream>();
+        e.to_string()
+    })
+}
+
+impl<'a> ScanVisitor<'a> {
+    fn new(file: &'a Path, include_tests: bool, occurrences: &'a mut Vec<Occurrence>) -> Self {
+        Self { file, include_tests, occurrences, actor_convention: Ok(Convention::V1) }
+    }
+
+    fn record(&mut self, name: &LitStr) {
+        self.record_with(name, &Ok(Convention::V1));
+    }
+
+    /// Records a name hashed with a hashing convention other than version 1
+    fn record_with(&mut self, name: &LitStr, convention: &Result<Convention, String>) {
+        let number = convention.clone().and_then(|convention| {
+            MethodResolver::with_convention(Blake2bHasher {}, convention)
+                .method_number(&name.value())
+                .map_err(|e| e.to_string())
+        });
+        self.occurrences.push(Occurrence {
+            name: name.value(),
+            convention: convention.clone().unwrap_or_default(),
+            number,
+            file: self.file.to_path_buf(),
+            line: name.span().start().line,
+        });
+    }
+
+    /// Records string literals in the token tree, e.g. the names in a `"A" | "B"` pattern
+    fn record_literals(&mut self, tokens: TokenStream, convention: &Result<Convention, String>) {
+        for token in tokens {
+            match token {
+                TokenTree::Literal(lit) => {
+                    if let Ok(name) = syn::parse2::<LitStr>(TokenTree::Literal(lit).into()) {
+                        self.record_with(&name, convention);
+                    }
+                }
+                TokenTree::Group(group) => self.record_literals(group.stream(), convention),
+                _ => {}
+            }
+        }
+    }
+
+    fn scan_macro(&mut self, name: &str, tokens: TokenStream) {
+        match name {
+            "method_hash" => {
+                if let Ok(args) = syn::parse2::<MethodHashArgs>(tokens) {
+                    self.record_with(&args.name, &args.convention);
+                }
+            }
+            "match_method" => {
+                // the method expression and convention arguments come before the arms
+                let mut args = TokenStream::new();
+                let mut arms = None;
+                let mut after_method = false;
+                for token in tokens {
+                    match token {
+                        TokenTree::Group(group) if group.delimiter() == Delimiter::Brace => {
+                            arms = Some(group);
+                            break;
+                        }
+                        TokenTree::Punct(p) if p.as_char() == ',' && !after_method => {
+                            after_method = true
+                        }
+                        token if after_method => args.extend([token]),
+                        _ => {}
+                    }
+                }
+                if let Some(arms) = arms {
+                    let convention =
+                        (|input: ParseStream| -> syn::Result<_> { Ok(read_convention(input)) })
+                            .parse2(args)
+                            .unwrap_or_else(|e: syn::Error| Err(e.to_string()));
+                    self.scan_match_arms(arms.stream(), &convention);
+                }
+            }
+            "actor_manifest" => {
+                if let Ok(declaration) = syn::parse2::<ManifestDeclaration>(tokens) {
+                    for method in declaration.methods.iter() {
+                        self.record(&method.name);
+                    }
+                }
+            }
+            _ => self.scan_tokens(tokens),
+        }
+    }
+
+    /// Records the names in the patterns of `match_method!` arms, scanning the arm bodies for
+    /// nested macros
+    fn scan_match_arms(&mut self, tokens: TokenStream, convention: &Result<Convention, String>) {
+        let mut tokens = tokens.into_iter().peekable();
+        loop {
+            // the pattern runs up to `=>`, stopping at an `if` guard
+            let mut pattern = TokenStream::new();
+            let mut in_guard = false;
+            loop {
+                match tokens.next() {
+                    None => return,
+                    Some(TokenTree::Punct(p))
+                        if p.as_char() == '=' && p.spacing() == Spacing::Joint =>
+                    {
+                        if matches!(tokens.peek(), Some(TokenTree::Punct(p)) if p.as_char() == '>')
+                        {
+                            tokens.next();
+                            break;
+                        }
+                    }
+                    Some(TokenTree::Ident(ident)) if ident == "if" => in_guard = true,
+                    Some(token) if !in_guard => pattern.extend([token]),
+                    Some(_) => {}
+                }
+            }
+            // an `exports(methods)` arm handles the standard Exports method
+            match pattern.clone().into_iter().next() {
+                Some(TokenTree::Ident(ident)) if ident == "exports" => {
+                    self.record_with(&LitStr::new(EXPORTS_METHOD_NAME, ident.span()), convention)
+                }
+                _ => self.record_literals(pattern, convention),
+            }
+
+            // the body is either a block or an expression up to the next top-level comma
+            let mut body = TokenStream::new();
+            match tokens.next() {
+                Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+                    body.extend([TokenTree::Group(group)]);
+                    if matches!(tokens.peek(), Some(TokenTree::Punct(p)) if p.as_char() == ',') {
+                        tokens.next();
+                    }
+                }
+                Some(first) => {
+                    body.extend([first]);
+                    for token in tokens.by_ref() {
+                        match token {
+                            TokenTree::Punct(p) if p.as_char() == ',' => break,
+                            token => body.extend([token]),
+                        }
+                    }
+                }
+                None => return,
+            }
+            self.scan_tokens(body);
+        }
+    }
+
+    /// Looks for macro invocations inside the tokens of another macro
+    fn scan_tokens(&mut self, tokens: TokenStream) {
+        let tokens = tokens.into_iter().collect::<Vec<_>>();
+        let mut i = 0;
+        while i < tokens.len() {
+            match (&tokens[i], tokens.get(i + 1), tokens.get(i + 2)) {
+                (
+                    TokenTree::Ident(ident),
+                    Some(TokenTree::Punct(bang)),
+                    Some(TokenTree::Group(group)),
+                ) if bang.as_char() == '!' => {
+                    self.scan_macro(&ident.to_string(), group.stream());
+                    i += 3;
+                }
+                (TokenTree::Group(group), _, _) => {
+                    self.scan_tokens(group.stream());
+                    i += 1;
+                }
+                _ => i += 1,
+            }
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for ScanVisitor<'_> {
+    fn visit_item_mod(&mut self, item: &'ast ItemMod) {
+        if self.include_tests || !is_test_module(item) {
+            syn::visit::visit_item_mod(self, item);
+        }
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast ItemImpl) {
+        let actor = item
+            .attrs
+            .iter()
+            .find(|attr| attr.path.segments.last().is_some_and(|segment| segment.ident == "actor"));
+        let outer = match actor {
+            Some(attr) => {
+                let convention = actor_convention(attr).map_err(|e| e.to_string());
+                Some(std::mem::replace(&mut self.actor_convention, convention))
+            }
+            None => None,
+        };
+        syn::visit::visit_item_impl(self, item);
+        if let Some(outer) = outer {
+            self.actor_convention = outer;
+        }
+    }
+
+    fn visit_attribute(&mut self, attr: &'ast Attribute) {
+        if attr.path.is_ident("method") {
+            if let Ok(method) = attr.parse_args::<MethodAttr>() {
+                let convention = self.actor_convention.clone();
+                self.record_with(&method.name, &convention);
+            }
+        }
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        match mac.path.segments.last() {
+            Some(segment) => self.scan_macro(&segment.ident.to_string(), mac.tokens.clone()),
+            None => self.scan_tokens(mac.tokens.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use frc42_dispatch::convention::Convention;
+    use frc42_dispatch::method_hash;
+    use syn::visit::Visit;
+
+    use super::{problems, Occurrence, ScanVisitor};
+
+    fn scan_source(source: &str) -> Vec<Occurrence> {
+        let mut occurrences = Vec::new();
+        let file = Path::new("lib.rs");
+        let mut visitor = ScanVisitor::new(file, false, &mut occurrences);
+        visitor.visit_file(&syn::parse_file(source).unwrap());
+        occurrences
+    }
+
+    #[test]
+    fn finds_method_names() {
+        let occurrences = scan_source(
+            r#"
+const NAME: u64 = method_hash!("Name");
+const ACME_NAME: u64 = method_hash!("Name", prefix = "acme|");
+
+fn invoke(method: u64) -> u32 {
+    match_method!(method, {
+        "Constructor" => constructor(),
+        "Transfer" => {
+            let n = method_hash!("Receive");
+            transfer(n)
+        }
+        exports(methods) => exports(methods),
+        _ => abort(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    const IGNORED: u64 = method_hash!("Ignored");
+}
+"#,
+        );
+
+        let names = occurrences.iter().map(|o| (o.name.as_str(), o.line)).collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                ("Name", 2),
+                ("Name", 3),
+                ("Constructor", 7),
+                ("Transfer", 8),
+                ("Receive", 9),
+                ("Exports", 12)
+            ]
+        );
+        // the private namespace resolves the same name to a different number
+        assert_ne!(occurrences[0].number, occurrences[1].number);
+        assert!(problems(&occurrences).is_empty());
+    }
+
+    #[test]
+    fn flags_problems() {
+        let occurrences = scan_source(
+            r#"
+fn invoke(method: u64) -> u32 {
+    assert_eq!(method_hash!("Method46776"), 1);
+    match_method!(method, {
+        "Method85157" => one(),
+        "Method85157" => two(),
+        "badName" => three(),
+        _ => abort(),
+    })
+}
+"#,
+        );
+
+        let problems = problems(&occurrences);
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].starts_with("invalid method name \"badName\" at lib.rs:7"));
+        assert!(problems[1].contains("\"Method46776\" (lib.rs:3)"));
+        assert!(problems[1].contains("\"Method85157\" (lib.rs:5, lib.rs:6)"));
+    }
+
+    #[test]
+    fn compares_numbers_within_a_convention() {
+        let occurrences = scan_source(
+            r#"
+#[actor(prefix = "acme|")]
+impl Actor {
+    #[method("Transfer")]
+    fn transfer() {}
+}
+
+fn invoke(method: u64) -> u32 {
+    match_method!(method, prefix = "acme|", {
+        "Transfer" => transfer(),
+        "Method46776" => one(),
+        _ => abort(),
+    })
+}
+
+const ONE: u64 = method_hash!("Method85157");
+const TWO: u64 = method_hash!("Method46776", version = 2);
+"#,
+        );
+
+        let transfer = method_hash!("Transfer", prefix = "acme|");
+        assert_eq!(occurrences[0].number, Ok(transfer));
+        assert_eq!(occurrences[1].number, Ok(transfer));
+        assert_eq!(occurrences[2].convention, Convention::V1.with_prefix("acme|"));
+        // "Method85157" and "Method46776" only collide under version 1
+        let problems = problems(&occurrences);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("invalid method name \"Method46776\" at lib.rs:17"));
+    }
+}
@@ -0,0 +1,51 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use syn::ItemMod;
+
+/// Recursively lists the `.rs` files under a directory in a stable order
+///
+/// Hidden directories and cargo `target` directories are skipped.
+pub fn rust_sources(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    if dir.is_file() {
+        files.push(dir.to_path_buf());
+    } else {
+        collect_sources(dir, &mut files)?;
+    }
+    Ok(files)
+}
+
+fn collect_sources(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let mut entries = fs::read_dir(dir)
+        .with_context(|| format!("failed to read {}", dir.display()))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+            if name != "target" && !name.starts_with('.') {
+                collect_sources(&path, files)?;
+            }
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Parses a Rust source file
+pub fn parse_file(path: &Path) -> Result<syn::File> {
+    let source =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    syn::parse_file(&source).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+/// Whether a module is only compiled for tests (`#[cfg(test)]`)
+pub fn is_test_module(item: &ItemMod) -> bool {
+    item.attrs.iter().any(|attr| {
+        attr.path.is_ident("cfg") && attr.tokens.to_string().replace(' ', "") == "(test)"
+    })
+}
@@ -1,6 +1,10 @@
+use frc42_dispatch::interface::{
+    InterfaceId, INVALID_INTERFACE_ID, SUPPORTS_INTERFACE_ID, SUPPORTS_INTERFACE_METHOD_NUM,
+};
 use frc42_dispatch::method_hash;
+use frc46_token::token::FRC46_INTERFACE_ID;
 use frc53_nft::types::{ListTokensParams, ListTokensReturn};
-use frc53_nft::{types::MintReturn, types::TokenID};
+use frc53_nft::{types::MintReturn, types::TokenID, FRC53_INTERFACE_ID};
 use fvm_integration_tests::{dummy::DummyExterns, tester::Account};
 use fvm_ipld_bitfield::bitfield;
 use fvm_ipld_blockstore::MemoryBlockstore;
@@ -172,3 +176,29 @@ fn test_nft_actor() {
         assert_eq!(list_tokens_result.tokens, bitfield![1, 1, 1, 1]);
     }
 }
+
+#[test]
+fn test_nft_actor_supports_interface() {
+    let blockstore = MemoryBlockstore::default();
+    let mut tester = construct_tester(&blockstore);
+    let minter: [Account; 1] = tester.create_accounts().unwrap();
+
+    let actor_address = tester.install_actor_stateless(BASIC_NFT_ACTOR_BINARY, 10_000);
+    tester.instantiate_machine(DummyExterns).unwrap();
+    tester.call_method_ok(minter[0].1, actor_address, method_hash!("Constructor"), None);
+
+    let mut supports = |id: InterfaceId| {
+        let params = RawBytes::serialize(id).unwrap();
+        let ret_val = tester.call_method_ok(
+            minter[0].1,
+            actor_address,
+            SUPPORTS_INTERFACE_METHOD_NUM,
+            Some(params),
+        );
+        ret_val.msg_receipt.return_data.deserialize::<bool>().unwrap()
+    };
+    assert!(supports(FRC53_INTERFACE_ID));
+    assert!(supports(SUPPORTS_INTERFACE_ID));
+    assert!(!supports(FRC46_INTERFACE_ID));
+    assert!(!supports(INVALID_INTERFACE_ID));
+}
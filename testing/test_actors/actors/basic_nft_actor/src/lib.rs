@@ -1,3 +1,4 @@
+use frc42_dispatch::entrypoint::handle_supports_interface;
 use frc42_dispatch::match_method;
 use frc53_nft::{
     state::NFTState,
@@ -7,7 +8,7 @@ use frc53_nft::{
         ListTokensParams, RevokeForAllParams, RevokeParams, TokenID, TransferFromParams,
         TransferParams,
     },
-    NFT,
+    FRC53_INTERFACE_ID, NFT,
 };
 use fvm_actor_utils::{
     blockstore::Blockstore, messaging::FvmMessenger, syscalls::fvm_syscalls::FvmSyscalls,
@@ -182,6 +183,9 @@ fn invoke(params: u32) -> u32 {
             let res = handle.list_account_operators(&params.owner, params.cursor, params.limit).unwrap();
             return_ipld(&res).unwrap()
         }
+        "SupportsInterface" => {
+            handle_supports_interface(&[FRC53_INTERFACE_ID], params)
+        }
         _ => {
             sdk::vm::abort(ExitCode::USR_ILLEGAL_ARGUMENT.value(), Some(&format!("Unknown method number {method_num:?} was invoked")));
         }
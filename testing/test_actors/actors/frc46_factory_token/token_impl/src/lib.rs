@@ -11,6 +11,7 @@ use frc46_token::token::{
     Token, TokenError,
 };
 use fvm_actor_utils::{
+    blockstore::CommitBlockstore,
     messaging::MessagingError,
     receiver::ReceiverHookError,
     syscalls::Syscalls,
@@ -111,7 +112,7 @@ pub struct ConstructorParams {
     pub minter: Address,
 }
 
-pub fn construct_token<S: Syscalls, BS: Blockstore>(
+pub fn construct_token<S: Syscalls, BS: CommitBlockstore>(
     runtime: ActorRuntime<S, BS>,
     params: ConstructorParams,
 ) -> Result<u32, RuntimeError> {
@@ -158,7 +159,7 @@ impl FactoryTokenState {
 /// Implementation of the token API in a FVM actor
 ///
 /// Here the Ipld parameter structs are marshalled and passed to the underlying library functions
-impl<SC: Syscalls, BS: Blockstore> FRC46Token for FactoryToken<SC, BS> {
+impl<SC: Syscalls, BS: CommitBlockstore> FRC46Token for FactoryToken<SC, BS> {
     type TokenError = RuntimeError;
     fn name(&self) -> String {
         self.state.name.clone()
@@ -280,7 +281,7 @@ pub struct MintParams {
     pub operator_data: RawBytes,
 }
 
-impl<S: Syscalls, BS: Blockstore> FactoryToken<S, BS> {
+impl<S: Syscalls, BS: CommitBlockstore> FactoryToken<S, BS> {
     pub fn new(
         runtime: ActorRuntime<S, BS>,
         name: String,